@@ -0,0 +1,153 @@
+/// ApproxMedianHeap is a struct that keeps track of an approximate running median using bounded memory.
+/// It's intended for huge streams where storing every value in a MedianHeap isn't an option.
+///
+/// Under the hood it uses a simplified Greenwald-Khanna quantile summary, which stores a small sorted sample of the values together with bounds on their ranks.
+/// It merges any neighbouring samples whose rank bounds stay within the error bound, but leaves out the band condition of the full algorithm.
+/// On typical streams the sample stays close to the O((1/epsilon) * log(epsilon * n)) of Greenwald-Khanna, but that size isn't guaranteed, and adversarial orders can make it grow further.
+/// The error bound below holds regardless of the size of the sample.
+///
+/// # Error bound
+/// For a heap containing n values, the value returned by get_median has a rank that differs from the rank of the true median by at most epsilon * n.
+/// For example, with an epsilon of 0.01 and 1 000 000 values, the returned value lies between the 490 000th and the 510 000th smallest value.
+///
+/// Example:
+/// ```
+/// use median_heap::ApproxMedianHeap;
+///
+/// let mut heap = ApproxMedianHeap::new(0.01);
+/// for value in 1..=1000 {
+///   heap.push(value);
+/// }
+///
+/// let median = heap.get_median().unwrap();
+/// assert!((490..=510).contains(&median));
+/// ```
+#[derive(Clone, Debug)]
+pub struct ApproxMedianHeap<T> {
+  epsilon: f64,
+  count: usize,
+  // Sorted tuples of (value, g, delta) as described by Greenwald and Khanna.
+  // g is the difference between the minimum rank of this value and the previous one, delta is the uncertainty in its rank.
+  pub(crate) summary: Vec<(T, usize, usize)>,
+}
+
+impl<T: Ord + Copy> ApproxMedianHeap<T> {
+  /// Creates a new ApproxMedianHeap with the given error bound.
+  ///
+  /// # Panics
+  /// Panics if epsilon isn't in the range (0, 1).
+  pub fn new(epsilon: f64) -> Self {
+    assert!(epsilon > 0.0 && epsilon < 1.0, "epsilon must be in the range (0, 1)");
+    ApproxMedianHeap {
+      epsilon,
+      count: 0,
+      summary: Vec::new(),
+    }
+  }
+
+  /// Adds a value to the heap.
+  ///
+  /// # Complexity
+  /// O(s) amortized, where s is the number of stored samples
+  pub fn push(&mut self, value: T) {
+    let index = self.summary.partition_point(|x| x.0 <= value);
+    // The smallest and largest values are known exactly, every other value inherits the current uncertainty.
+    let delta = if index == 0 || index == self.summary.len() {
+      0
+    } else {
+      self.threshold()
+    };
+    self.summary.insert(index, (value, 1, delta));
+    self.count += 1;
+
+    let period = ((1.0 / (2.0 * self.epsilon)) as usize).max(1);
+    if self.count.is_multiple_of(period) {
+      self.compress();
+    }
+  }
+
+  /// Returns an approximation of the median of the values in the heap.
+  /// If the heap is empty, the method returns None.
+  ///
+  /// # Complexity
+  /// O(s), where s is the number of stored samples
+  pub fn get_median(&self) -> Option<T> {
+    if self.summary.is_empty() {
+      return None
+    }
+
+    let rank = self.count.div_ceil(2);
+    let tolerance = (self.epsilon * self.count as f64) as usize;
+    let mut min_rank = 0;
+    for &(value, g, delta) in &self.summary {
+      min_rank += g;
+      if min_rank + delta > rank + tolerance {
+        break;
+      }
+      if rank <= min_rank + tolerance {
+        return Some(value)
+      }
+    }
+
+    Some(self.summary[self.summary.len() - 1].0)
+  }
+
+  /// Returns the error bound this heap was created with.
+  pub fn epsilon(&self) -> f64 {
+    self.epsilon
+  }
+
+  // Merges neighbouring tuples whose combined rank uncertainty stays within the error bound.
+  // The tuples are walked from the largest value down, merging every tuple into the kept tuple after it, while the kept tuples are packed toward the end.
+  // The first and the last tuple are always kept, so the smallest and largest values stay exact.
+  //
+  // # Complexity
+  // O(s), where s is the number of tuples
+  fn compress(&mut self) {
+    let len = self.summary.len();
+    if len < 3 {
+      return
+    }
+
+    let threshold = self.threshold();
+    let mut kept = len - 1;
+    for i in (1..len - 1).rev() {
+      let (_, g, _) = self.summary[i];
+      let (_, next_g, next_delta) = self.summary[kept];
+      if g + next_g + next_delta <= threshold {
+        self.summary[kept].1 += g;
+      } else {
+        kept -= 1;
+        self.summary[kept] = self.summary[i];
+      }
+    }
+    kept -= 1;
+    self.summary[kept] = self.summary[0];
+    self.summary.drain(..kept);
+  }
+
+  fn threshold(&self) -> usize {
+    (2.0 * self.epsilon * self.count as f64) as usize
+  }
+}
+
+impl<T> ApproxMedianHeap<T> {
+  /// Returns the number of values pushed to the heap.
+  ///
+  /// # Complexity
+  /// O(1)
+  pub fn len(&self) -> usize {
+    self.count
+  }
+
+  /// Returns true if no values have been pushed to the heap, false otherwise.
+  pub fn is_empty(&self) -> bool {
+    self.count == 0
+  }
+
+  /// Removes all values from the heap.
+  pub fn clear(&mut self) {
+    self.count = 0;
+    self.summary.clear();
+  }
+}
@@ -0,0 +1,152 @@
+use std::marker::PhantomData;
+use std::ops::Sub;
+
+use crate::medianheap::{compare_distances, CopyValue, DistanceComparator};
+use crate::{AutoCapacity, EvictionPolicy, MedianHeap, MergeMedian};
+
+/// MedianHeapBuilder is a struct that configures a MedianHeap step by step before creating it.
+/// It's the recommended way to create a heap that needs more than a median kind, since every option is a chainable method instead of another constructor.
+///
+/// - median_kind sets the median kind, which defaults to the default value of K.
+/// - capacity bounds the number of values the heap holds, which is unbounded by default.
+/// - policy sets the eviction policy used once the capacity is reached, which defaults to EvictionPolicy::EvictOldest.
+/// - evict_farthest_from_median sets the policy to EvictionPolicy::EvictFarthestFromMedian, which is only available for values that can be subtracted.
+/// - window bounds the heap to the most recent values, the same as a capacity with EvictionPolicy::EvictOldest.
+/// - max_duplicates caps the number of copies of any single value, see MedianHeap::with_max_duplicates.
+/// - slack lets the halves drift further apart before they're rebalanced, see MedianHeap::with_slack.
+/// - auto_capacity lets the heap shrink and grow its allocation on its own, see AutoCapacity.
+///
+/// A builder without a capacity creates the same unbounded heap as MedianHeap::new, and the policy is ignored.
+///
+/// Example:
+/// ```
+/// use median_heap::{EvictionPolicy, MedianHeap, MedianHeapBuilder, IntegerMidpointMedian};
+///
+/// let mut window: MedianHeap<i32, IntegerMidpointMedian> = MedianHeap::builder().window(3).build();
+/// for value in [1, 2, 3, 10] {
+///   window.push(value);
+/// }
+/// assert_eq!(3, window.get_median().unwrap());
+///
+/// let mut bounded = MedianHeapBuilder::new(IntegerMidpointMedian)
+///   .capacity(3)
+///   .policy(EvictionPolicy::EvictExtreme)
+///   .build();
+/// for value in [5, 6, 7, 100] {
+///   bounded.push(value);
+/// }
+/// assert!(!bounded.contains(&100));
+/// ```
+#[derive(Clone, Debug)]
+pub struct MedianHeapBuilder<T, K> {
+  median_kind: K,
+  capacity: Option<usize>,
+  policy: EvictionPolicy,
+  // Set by evict_farthest_from_median, see MedianHeap::with_farthest_eviction.
+  compare_distances: Option<DistanceComparator<T>>,
+  // The cap set by max_duplicates and the function copying a value into the map counting the copies.
+  max_duplicates: Option<(usize, CopyValue<T>)>,
+  slack: usize,
+  auto_capacity: Option<AutoCapacity>,
+  values: PhantomData<T>,
+}
+
+impl<T, K: Default> Default for MedianHeapBuilder<T, K> {
+  fn default() -> Self {
+    MedianHeapBuilder::new(K::default())
+  }
+}
+
+impl<T, K> MedianHeapBuilder<T, K> {
+  /// Creates a new MedianHeapBuilder instance with the specified median kind and no other options set.
+  pub fn new(median_kind: K) -> Self {
+    MedianHeapBuilder {
+      median_kind,
+      capacity: None,
+      policy: EvictionPolicy::EvictOldest,
+      compare_distances: None,
+      max_duplicates: None,
+      slack: 1,
+      auto_capacity: None,
+      values: PhantomData,
+    }
+  }
+
+  /// Sets the median kind of the heap.
+  pub fn median_kind(mut self, median_kind: K) -> Self {
+    self.median_kind = median_kind;
+    self
+  }
+
+  /// Bounds the heap to at most capacity values, evicting values with the configured policy.
+  pub fn capacity(mut self, capacity: usize) -> Self {
+    self.capacity = Some(capacity);
+    self
+  }
+
+  /// Sets the policy that decides which value is evicted once the capacity is reached.
+  /// EvictionPolicy::EvictFarthestFromMedian has to be set with evict_farthest_from_median instead.
+  pub fn policy(mut self, policy: EvictionPolicy) -> Self {
+    self.policy = policy;
+    self
+  }
+
+  /// Bounds the heap to the size most recent values, turning it into a sliding window.
+  /// This sets both the capacity and the policy, so a later call to either overrides it.
+  pub fn window(self, size: usize) -> Self {
+    self.capacity(size).policy(EvictionPolicy::EvictOldest)
+  }
+
+  /// Sets how many values the halves of the heap may drift apart before they're rebalanced, which defaults to 1.
+  /// See MedianHeap::with_slack.
+  pub fn slack(mut self, slack: usize) -> Self {
+    self.slack = slack;
+    self
+  }
+
+  /// Lets the heap shrink its allocation after a burst and grow it back when it fills up again, according to the configuration.
+  /// See MedianHeap::with_auto_capacity.
+  pub fn auto_capacity(mut self, config: AutoCapacity) -> Self {
+    self.auto_capacity = Some(config);
+    self
+  }
+}
+
+impl<T: Copy, K> MedianHeapBuilder<T, K> {
+  /// Caps the number of copies of any single value the heap holds, so pushing a value it already holds max_duplicates times is a no-op.
+  /// See MedianHeap::with_max_duplicates.
+  pub fn max_duplicates(mut self, max_duplicates: usize) -> Self {
+    self.max_duplicates = Some((max_duplicates, |x| *x));
+    self
+  }
+}
+
+impl<T: Ord + Copy + Sub<Output = T>, K> MedianHeapBuilder<T, K> {
+  /// Sets the policy to EvictionPolicy::EvictFarthestFromMedian, which measures the distances to the median by subtracting values.
+  /// A later call to policy or window overrides it.
+  pub fn evict_farthest_from_median(mut self) -> Self {
+    self.compare_distances = Some(compare_distances);
+    self.policy(EvictionPolicy::EvictFarthestFromMedian)
+  }
+}
+
+impl<T: Ord, K: MergeMedian<T>> MedianHeapBuilder<T, K> {
+  /// Creates the configured MedianHeap.
+  ///
+  /// # Panics
+  /// Panics if the capacity, max_duplicates or slack is 0, if EvictionPolicy::EvictFarthestFromMedian was set with policy instead of evict_farthest_from_median, or if the AutoCapacity configuration is rejected by MedianHeap::with_auto_capacity.
+  pub fn build(self) -> MedianHeap<T, K> {
+    let mut heap = match self.capacity {
+      Some(capacity) => MedianHeap::bounded(self.median_kind, capacity, self.policy, self.compare_distances),
+      None => MedianHeap::new(self.median_kind),
+    };
+    if let Some((max_duplicates, copy)) = self.max_duplicates {
+      heap.cap_duplicates(max_duplicates, copy);
+    }
+    heap.set_slack(self.slack);
+    if let Some(config) = self.auto_capacity {
+      heap.manage_capacity(config);
+    }
+    heap
+  }
+}
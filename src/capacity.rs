@@ -0,0 +1,47 @@
+/// AutoCapacity is a struct that configures how a MedianHeap created with MedianHeap::with_auto_capacity or MedianHeapBuilder::auto_capacity manages its capacity.
+/// It's meant for long-running services with a fluctuating load, where the heap should give memory back after a burst without calling shrink_to_fit by hand.
+///
+/// Every method adding or removing values checks how much of the capacity is in use.
+/// Once the heap has held fewer than shrink_below of its capacity for shrink_after operations in a row, it shrinks to twice its length, but never below min_capacity.
+/// The heap is then half full, so with shrink_below at most one half it can't shrink again before the usage drops further and stays down for another shrink_after operations.
+/// If the heap fills up again after shrinking, it grows back to the largest length it held before the shrink in a single allocation, instead of doubling step by step.
+///
+/// Example:
+/// ```
+/// # #[cfg(feature = "std")] {
+/// use median_heap::{AutoCapacity, MedianHeap, LeftHandedMedian};
+///
+/// let config = AutoCapacity { shrink_below: 0.25, shrink_after: 10, min_capacity: 4 };
+/// let mut heap = MedianHeap::with_auto_capacity(LeftHandedMedian, config);
+/// heap.push_all(0..1000);
+/// while heap.len() > 10 {
+///   heap.pop();
+/// }
+/// for _ in 0..10 {
+///   heap.push(1);
+///   heap.pop();
+/// }
+///
+/// assert!(heap.capacity() < 100);
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AutoCapacity {
+  /// The fraction of the capacity below which the usage counts as low, greater than 0 and at most 0.5.
+  pub shrink_below: f64,
+  /// The number of consecutive operations with a low usage after which the heap shrinks.
+  pub shrink_after: usize,
+  /// The capacity the heap never shrinks below.
+  pub min_capacity: usize,
+}
+
+impl Default for AutoCapacity {
+  /// Shrinks the heap once it has been less than a quarter full for 1000 operations in a row, down to at least 16 values.
+  fn default() -> Self {
+    AutoCapacity {
+      shrink_below: 0.25,
+      shrink_after: 1000,
+      min_capacity: 16,
+    }
+  }
+}
@@ -0,0 +1,137 @@
+use std::collections::BTreeMap;
+
+use crate::MergeMedian;
+
+/// DecayedMedianHeap is a struct that keeps track of an exponentially weighted median, the median analog of an exponentially weighted moving average.
+/// Every push multiplies the weights of the values already pushed by the decay factor lambda and adds the new value with a weight of 1.
+/// The weighted median is the value that splits the total weight in half, so it follows recent values more closely than older ones.
+///
+/// Lambda lies in (0, 1].
+/// The weights add up to at most 1 / (1 - lambda), which is the effective window length, and the most recent ln(2) / ln(1 / lambda) values hold half of the total weight.
+/// A lambda of 0.9 therefore behaves like a window of about 10 values, and a lambda of 1 doesn't decay at all, giving the plain median of every value.
+///
+/// Instead of decaying every stored weight, new values get a weight that grows by 1 / lambda on every push, which is equivalent once the weights are compared.
+/// The weights are rescaled before they overflow, at which point values whose weight has become negligible are dropped.
+///
+/// Like MedianHeap, the struct takes a type K implementing the MergeMedian trait.
+/// It's used when the total weight is split exactly between two values.
+///
+/// Example:
+/// ```
+/// use median_heap::{LeftHandedMedian, MedianHeap};
+///
+/// let mut heap = MedianHeap::with_decay(LeftHandedMedian, 0.5);
+/// for value in [1, 1, 1, 1, 9, 9] {
+///   heap.push(value);
+/// }
+///
+/// // The two most recent values hold 3 / 4 of the weight.
+/// assert_eq!(9, heap.get_median().unwrap());
+/// ```
+#[derive(Clone, Debug)]
+pub struct DecayedMedianHeap<T, K> {
+  median_kind: K,
+  lambda: f64,
+  weights: BTreeMap<T, f64>,
+  total_weight: f64,
+  // The weight of the next value, relative to the stored weights.
+  next_weight: f64,
+  count: usize,
+}
+
+// The relative weight at which the weights are rescaled, far below the f64 range.
+const RESCALE_THRESHOLD: f64 = 1e100;
+
+impl<T: Ord + Copy, K: MergeMedian<T>> DecayedMedianHeap<T, K> {
+  /// Creates a new DecayedMedianHeap instance with the specified median kind and decay factor.
+  ///
+  /// # Panics
+  /// Panics if lambda isn't in (0, 1], or if it's subnormal, since the weights grow by 1 / lambda, which overflows to infinity for subnormal values.
+  pub fn new(median_kind: K, lambda: f64) -> Self {
+    assert!(lambda > 0.0 && lambda <= 1.0, "lambda must be in (0, 1]");
+    assert!(lambda >= f64::MIN_POSITIVE, "lambda must not be subnormal");
+    DecayedMedianHeap {
+      median_kind,
+      lambda,
+      weights: BTreeMap::new(),
+      total_weight: 0.0,
+      next_weight: 1.0,
+      count: 0,
+    }
+  }
+
+  /// Adds a value to the heap, decaying the weights of the values already pushed.
+  ///
+  /// # Complexity
+  /// O(logn), and O(n) in the number of distinct values when the weights are rescaled
+  pub fn push(&mut self, value: T) {
+    *self.weights.entry(value).or_insert(0.0) += self.next_weight;
+    self.total_weight += self.next_weight;
+    self.next_weight /= self.lambda;
+    self.count += 1;
+
+    if self.next_weight > RESCALE_THRESHOLD {
+      self.rescale();
+    }
+  }
+
+  /// Returns the exponentially weighted median of the values in the heap.
+  /// If the heap is empty, the method returns None.
+  ///
+  /// # Complexity
+  /// O(n) in the number of distinct values
+  pub fn get_median(&self) -> Option<T> {
+    let mut weight = 0.0;
+    let mut values = self.weights.iter();
+    while let Some((value, value_weight)) = values.next() {
+      weight += value_weight;
+      if weight * 2.0 == self.total_weight {
+        // The total weight is split exactly between this value and the next one.
+        let next = values.next().map_or(value, |(next, _)| next);
+        return Some(self.median_kind.merge(value, next))
+      }
+      if weight * 2.0 > self.total_weight {
+        return Some(*value)
+      }
+    }
+
+    None
+  }
+
+  // Divides every weight by the weight of the next value, dropping values whose weight no longer affects the total.
+  fn rescale(&mut self) {
+    let scale = self.next_weight;
+    let negligible = self.total_weight / scale * f64::EPSILON;
+    self.weights.retain(|_, weight| {
+      *weight /= scale;
+      *weight > negligible
+    });
+    self.total_weight = self.weights.values().sum();
+    self.next_weight = 1.0;
+  }
+}
+
+impl<T, K> DecayedMedianHeap<T, K> {
+  /// Returns the number of values pushed to the heap, including the ones whose weight has decayed away.
+  pub fn len(&self) -> usize {
+    self.count
+  }
+
+  /// Returns true if no values have been pushed to the heap, false otherwise.
+  pub fn is_empty(&self) -> bool {
+    self.count == 0
+  }
+
+  /// Returns the decay factor of the heap.
+  pub fn lambda(&self) -> f64 {
+    self.lambda
+  }
+
+  /// Removes all values from the heap.
+  pub fn clear(&mut self) {
+    self.weights.clear();
+    self.total_weight = 0.0;
+    self.next_weight = 1.0;
+    self.count = 0;
+  }
+}
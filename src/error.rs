@@ -0,0 +1,56 @@
+use core::error::Error;
+use core::fmt::{Debug, Display, Formatter};
+
+/// MedianHeapError is an enum that describes why a fallible MedianHeap operation failed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MedianHeapError {
+  /// The heap doesn't contain any values.
+  Empty,
+  /// The heap doesn't contain the requested value.
+  NotFound,
+}
+
+impl Display for MedianHeapError {
+  fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+    match self {
+      MedianHeapError::Empty => write!(f, "the heap is empty"),
+      MedianHeapError::NotFound => write!(f, "the value was not found in the heap"),
+    }
+  }
+}
+
+impl Error for MedianHeapError {}
+
+/// FromPartsError is an enum that describes why MedianHeap::try_from_parts rejected the heaps it was given.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FromPartsError<T> {
+  /// The heaps differ in length by more than one value.
+  Unbalanced {
+    /// The number of values in the max heap.
+    max_len: usize,
+    /// The number of values in the min heap.
+    min_len: usize,
+  },
+  /// The root of the max heap is greater than the root of the min heap, so the lower half holds a value above the upper half.
+  Misordered {
+    /// The root of the max heap, the largest value of the lower half.
+    max_root: T,
+    /// The root of the min heap, the smallest value of the upper half.
+    min_root: T,
+  },
+}
+
+impl<T: Debug> Display for FromPartsError<T> {
+  fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+    match self {
+      FromPartsError::Unbalanced { max_len, min_len } => {
+        write!(f, "the max heap holds {} values and the min heap {}, which differ by more than one", max_len, min_len)
+      }
+      FromPartsError::Misordered { max_root, min_root } => {
+        write!(f, "the root of the max heap {:?} is greater than the root of the min heap {:?}", max_root, min_root)
+      }
+    }
+  }
+}
+
+impl<T: Debug> Error for FromPartsError<T> {}
@@ -0,0 +1,32 @@
+/// EvictionPolicy is an enum that decides which value a capacity-bounded MedianHeap removes when a push would exceed its capacity.
+/// The new value is added first, after which the policy picks the value to evict, so some policies can evict the new value itself.
+///
+/// Example:
+/// ```
+/// # #[cfg(feature = "std")] {
+/// use median_heap::{EvictionPolicy, MedianHeap, IntegerMidpointMedian};
+///
+/// let mut heap = MedianHeap::with_policy(IntegerMidpointMedian, 3, EvictionPolicy::EvictOldest);
+/// heap.push(1);
+/// heap.push(2);
+/// heap.push(3);
+/// heap.push(10);
+///
+/// assert_eq!(3, heap.len());
+/// assert!(!heap.contains(&1));
+/// assert_eq!(3, heap.get_median().unwrap());
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EvictionPolicy {
+  /// Evicts the value that was pushed the longest time ago, which turns the heap into a sliding window over the most recent values.
+  EvictOldest,
+  /// Evicts the smallest or the largest value, whichever is farther from the current median.
+  /// If both are equally far away, the smallest value is evicted.
+  /// Measuring the distances subtracts values, so a heap with this policy is created with MedianHeap::with_farthest_eviction or MedianHeapBuilder::evict_farthest_from_median.
+  EvictFarthestFromMedian,
+  /// Evicts the extreme value on the side of the median the new value was added to.
+  /// Pushing a value above the median evicts the largest value and pushing any other value evicts the smallest one.
+  /// A new value that would become the new minimum or maximum is therefore evicted immediately, which rejects outliers.
+  EvictExtreme,
+}
@@ -0,0 +1,135 @@
+use std::cmp::Ordering;
+
+use crate::{FloatMidpointMedian, MedianHeap, MergeMedian};
+
+/// FloatMedianHeap is a struct that keeps track of the median of f64 values.
+/// It orders the values with f64::total_cmp, which gives every f64 a place in a total order, so no wrapper type or NaN policy is needed.
+/// When two median candidates are found, the median is their midpoint.
+///
+/// # NaN and signed zeros
+/// total_cmp sorts -0.0 before +0.0, a positive NaN after every other value including infinity, and a negative NaN before every other value.
+/// NaNs therefore shift the median like any other extreme value, and a midpoint involving a NaN is NaN.
+/// NaN is never equal to any value other than a NaN with the same bit pattern, so delete and contains match NaNs bitwise.
+///
+/// Example:
+/// ```
+/// use median_heap::FloatMedianHeap;
+///
+/// let mut heap = FloatMedianHeap::new();
+/// heap.push(1.5);
+/// heap.push(f64::NAN);
+/// heap.push(0.5);
+/// heap.push(2.5);
+///
+/// // The positive NaN sorts after 2.5, so the median candidates are 1.5 and 2.5.
+/// assert_eq!(2.0, heap.get_median().unwrap());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct FloatMedianHeap {
+  heap: MedianHeap<TotalF64, FloatMidpointMedian>,
+}
+
+// An f64 ordered by f64::total_cmp.
+#[derive(Clone, Copy, Debug, Default)]
+struct TotalF64(f64);
+
+impl PartialEq for TotalF64 {
+  fn eq(&self, other: &Self) -> bool {
+    self.cmp(other) == Ordering::Equal
+  }
+}
+
+impl Eq for TotalF64 {}
+
+impl PartialOrd for TotalF64 {
+  fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+    Some(self.cmp(other))
+  }
+}
+
+impl Ord for TotalF64 {
+  fn cmp(&self, other: &Self) -> Ordering {
+    self.0.total_cmp(&other.0)
+  }
+}
+
+impl MergeMedian<TotalF64> for FloatMidpointMedian {
+  fn merge(&self, a: &TotalF64, b: &TotalF64) -> TotalF64 {
+    TotalF64(self.merge(&a.0, &b.0))
+  }
+}
+
+impl FloatMedianHeap {
+  /// Creates a new, empty FloatMedianHeap instance.
+  pub fn new() -> Self {
+    FloatMedianHeap {
+      heap: MedianHeap::new(FloatMidpointMedian),
+    }
+  }
+
+  /// Adds a value to the heap.
+  ///
+  /// # Complexity
+  /// O(logn)
+  pub fn push(&mut self, value: f64) {
+    self.heap.push(TotalF64(value));
+  }
+
+  /// Returns the median of the values in the heap.
+  /// If the heap is empty, the method returns None.
+  ///
+  /// # Complexity
+  /// O(1)
+  pub fn get_median(&self) -> Option<f64> {
+    self.heap.get_median().map(|x| x.0)
+  }
+
+  /// Removes the median of the values in the heap and returns it, in the same way as MedianHeap::pop.
+  ///
+  /// # Complexity
+  /// O(logn)
+  pub fn pop(&mut self) -> Option<f64> {
+    self.heap.pop().map(|x| x.0)
+  }
+
+  /// Removes every instance of the specified value from the heap.
+  ///
+  /// # Complexity
+  /// O(n)
+  pub fn delete(&mut self, value: f64) {
+    self.heap.delete(&TotalF64(value));
+  }
+
+  /// Returns true if the heap contains the specified value, false otherwise.
+  ///
+  /// # Complexity
+  /// O(n)
+  pub fn contains(&self, value: f64) -> bool {
+    self.heap.contains(&TotalF64(value))
+  }
+
+  /// Returns the number of values in the heap.
+  pub fn len(&self) -> usize {
+    self.heap.len()
+  }
+
+  /// Returns true if the heap is empty, false otherwise.
+  pub fn is_empty(&self) -> bool {
+    self.heap.is_empty()
+  }
+
+  /// Removes all values from the heap.
+  pub fn clear(&mut self) {
+    self.heap.clear();
+  }
+}
+
+impl FromIterator<f64> for FloatMedianHeap {
+  fn from_iter<I: IntoIterator<Item = f64>>(iter: I) -> Self {
+    let mut heap = FloatMedianHeap::new();
+    for value in iter {
+      heap.push(value);
+    }
+    heap
+  }
+}
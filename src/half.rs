@@ -0,0 +1,19 @@
+/// Half is an enum that names the half of a MedianHeap a value is stored in, as yielded by MedianHeap::tagged_iter.
+///
+/// Example:
+/// ```
+/// # #[cfg(feature = "std")] {
+/// use median_heap::{Half, MedianHeap, LeftHandedMedian};
+///
+/// let heap: MedianHeap<i32, LeftHandedMedian> = vec![1, 2, 3].into_iter().collect();
+///
+/// assert_eq!(1, heap.tagged_iter().filter(|(_, half)| *half == Half::Upper).count());
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Half {
+  /// The lower half of the values, stored in the max heap.
+  Lower,
+  /// The upper half of the values, stored in the min heap.
+  Upper,
+}
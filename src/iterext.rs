@@ -0,0 +1,40 @@
+use crate::{MedianHeap, MergeMedian};
+
+/// IteratorExt is an extension trait that collects an iterator into a MedianHeap with a specified median kind.
+/// collect only works for median kinds that implement Default, since FromIterator can't take arguments, so this covers median kinds that carry configuration.
+/// It's implemented for every iterator.
+///
+/// Example:
+/// ```
+/// use median_heap::{IteratorExt, MergeMedian};
+///
+/// // Merges the two candidates into whichever is closest to the target.
+/// struct ClosestTo {
+///   target: i32,
+/// }
+///
+/// impl MergeMedian<i32> for ClosestTo {
+///   fn merge(&self, a: &i32, b: &i32) -> i32 {
+///     if (a - self.target).abs() <= (b - self.target).abs() { *a } else { *b }
+///   }
+/// }
+///
+/// let heap = vec![1, 4, 6, 9].into_iter().collect_median(ClosestTo { target: 6 });
+///
+/// assert_eq!(6, heap.get_median().unwrap());
+/// ```
+pub trait IteratorExt: Iterator {
+  /// Creates a MedianHeap with the specified median kind holding the values of the iterator.
+  ///
+  /// # Complexity
+  /// O(nlogn)
+  fn collect_median<K: MergeMedian<Self::Item>>(self, median_kind: K) -> MedianHeap<Self::Item, K>
+  where
+    Self: Sized,
+    Self::Item: Ord + Copy,
+  {
+    MedianHeap::from_iter_with(median_kind, self)
+  }
+}
+
+impl<I: Iterator> IteratorExt for I {}
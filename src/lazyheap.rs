@@ -0,0 +1,287 @@
+use std::collections::binary_heap::Drain;
+use std::collections::{BTreeMap, BinaryHeap};
+use std::fmt::{self, Debug};
+
+// LazyHeap is a binary heap that removes values from anywhere in the heap in O(logn) amortized time, which BinaryHeap can't do without rebuilding itself.
+// A removed value that isn't the root stays in the heap and is recorded as pending, and it's dropped once it reaches the root.
+// The root is always a live value, so peek, push and pop behave exactly like they do on a BinaryHeap.
+// Once the pending values make up half of the heap, it's rebuilt without them, so the heap never holds more than twice its live values.
+pub(crate) struct LazyHeap<U> {
+  heap: BinaryHeap<U>,
+  // The number of pending copies of every removed value that's still in the heap.
+  removed: BTreeMap<U, usize>,
+  removed_len: usize,
+}
+
+impl<U> LazyHeap<U> {
+  pub(crate) fn new() -> Self {
+    LazyHeap {
+      heap: BinaryHeap::new(),
+      removed: BTreeMap::new(),
+      removed_len: 0,
+    }
+  }
+
+  // Returns the number of live values.
+  pub(crate) fn len(&self) -> usize {
+    self.heap.len() - self.removed_len
+  }
+
+  pub(crate) fn peek(&self) -> Option<&U> {
+    self.heap.peek()
+  }
+
+  pub(crate) fn capacity(&self) -> usize {
+    self.heap.capacity()
+  }
+
+  // Returns true if the next push reallocates the heap.
+  pub(crate) fn is_full(&self) -> bool {
+    self.heap.len() == self.heap.capacity()
+  }
+
+  pub(crate) fn reserve(&mut self, additional: usize) {
+    self.heap.reserve(additional);
+  }
+
+  // Pending values still take up space, so the heap never shrinks below the values it holds.
+  pub(crate) fn shrink_to(&mut self, min_capacity: usize) {
+    self.heap.shrink_to(min_capacity);
+  }
+
+  pub(crate) fn shrink_to_fit(&mut self) {
+    self.heap.shrink_to_fit();
+  }
+
+  pub(crate) fn clear(&mut self) {
+    self.heap.clear();
+    self.removed.clear();
+    self.removed_len = 0;
+  }
+}
+
+impl<U: Ord> LazyHeap<U> {
+  pub(crate) fn push(&mut self, value: U) {
+    self.heap.push(value);
+  }
+
+  pub(crate) fn pop(&mut self) -> Option<U> {
+    let value = self.heap.pop()?;
+    self.prune();
+    Some(value)
+  }
+
+  // Replaces the root with a value and returns the old root.
+  //
+  // # Panics
+  // Panics if the heap is empty.
+  pub(crate) fn replace_root(&mut self, value: U) -> U {
+    let root = std::mem::replace(&mut *self.heap.peek_mut().unwrap(), value);
+    self.prune();
+    root
+  }
+
+  // Removes a copy of a value the heap holds, which is pending until it reaches the root.
+  //
+  // # Complexity
+  // O(logn) amortized
+  pub(crate) fn remove(&mut self, value: U) {
+    if self.heap.peek() == Some(&value) {
+      self.pop();
+      return
+    }
+
+    *self.removed.entry(value).or_insert(0) += 1;
+    self.removed_len += 1;
+    if self.removed_len * 2 > self.heap.len() {
+      self.purge();
+    }
+  }
+
+  // Removes the first live value matching the predicate, popping it if it's the root and rebuilding the heap without it otherwise.
+  pub(crate) fn remove_first(&mut self, predicate: impl Fn(&U) -> bool) -> Option<U> {
+    self.purge();
+    let index = self.heap.iter().position(predicate)?;
+    if index == 0 {
+      return self.heap.pop()
+    }
+    let mut values = std::mem::take(&mut self.heap).into_vec();
+    let value = values.swap_remove(index);
+    self.heap = BinaryHeap::from(values);
+    Some(value)
+  }
+
+  // Removes the smallest value, which is one of the leaves, by rebuilding the heap without it.
+  pub(crate) fn remove_least(&mut self) -> Option<U> {
+    self.purge();
+    if self.heap.is_empty() {
+      return None
+    }
+
+    let mut values = std::mem::take(&mut self.heap).into_vec();
+    let index = values.iter().enumerate().min_by(|a, b| a.1.cmp(b.1)).map(|(i, _)| i).unwrap();
+    let value = values.swap_remove(index);
+    self.heap = BinaryHeap::from(values);
+    Some(value)
+  }
+
+  // Returns the live value n positions below the root in heap order, so n = 0 is the root.
+  // The children of the value at index i of the heap's array are at 2i + 1 and 2i + 2, and the next value in heap order is always a child of a value already visited.
+  // So only the visited values and their children are compared, instead of every value of the heap.
+  pub(crate) fn nth_from_root(&self, n: usize) -> Option<&U> {
+    let values = self.heap.as_slice();
+    if n == 0 {
+      return values.first()
+    }
+
+    let mut pending = self.pending();
+    let mut frontier = BinaryHeap::from([(values.first()?, 0)]);
+    let mut n = n;
+    loop {
+      let (value, index) = frontier.pop()?;
+      match pending.get_mut(value).filter(|count| **count > 0) {
+        Some(count) => *count -= 1,
+        None if n == 0 => return Some(value),
+        None => n -= 1,
+      }
+      for child in [2 * index + 1, 2 * index + 2] {
+        if let Some(value) = values.get(child) {
+          frontier.push((value, child));
+        }
+      }
+    }
+  }
+
+  // Returns an iterator over the live values in arbitrary order.
+  pub(crate) fn iter(&self) -> impl Iterator<Item = &U> {
+    let mut pending = self.pending();
+    self.heap.iter().filter(move |value| match pending.get_mut(*value) {
+      Some(count) if *count > 0 => {
+        *count -= 1;
+        false
+      }
+      _ => true,
+    })
+  }
+
+  pub(crate) fn retain(&mut self, keep: impl FnMut(&U) -> bool) {
+    self.purge();
+    self.heap.retain(keep);
+  }
+
+  pub(crate) fn drain(&mut self) -> Drain<'_, U> {
+    self.purge();
+    self.heap.drain()
+  }
+
+  pub(crate) fn into_sorted_vec(mut self) -> Vec<U> {
+    self.purge();
+    self.heap.into_sorted_vec()
+  }
+
+  pub(crate) fn into_vec(mut self) -> Vec<U> {
+    self.purge();
+    self.heap.into_vec()
+  }
+
+  // Returns the underlying binary heap after rebuilding it without its pending values.
+  pub(crate) fn purged(&mut self) -> &BinaryHeap<U> {
+    self.purge();
+    &self.heap
+  }
+
+  pub(crate) fn into_inner(mut self) -> BinaryHeap<U> {
+    self.purge();
+    self.heap
+  }
+
+  // Pops pending values off the root, so the root is live again.
+  fn prune(&mut self) {
+    while let Some(root) = self.heap.peek() {
+      let Some(count) = self.removed.get_mut(root) else {
+        return
+      };
+      *count -= 1;
+      if *count == 0 {
+        self.removed.remove(root);
+      }
+      self.removed_len -= 1;
+      self.heap.pop();
+    }
+  }
+
+  // Rebuilds the heap without its pending values.
+  fn purge(&mut self) {
+    if self.removed_len == 0 {
+      return
+    }
+
+    let mut removed = std::mem::take(&mut self.removed);
+    let mut values = std::mem::take(&mut self.heap).into_vec();
+    values.retain(|value| match removed.get_mut(value) {
+      Some(count) if *count > 0 => {
+        *count -= 1;
+        false
+      }
+      _ => true,
+    });
+    self.heap = BinaryHeap::from(values);
+    self.removed_len = 0;
+  }
+
+  // Returns the number of pending copies of every removed value, keyed by reference.
+  fn pending(&self) -> BTreeMap<&U, usize> {
+    self.removed.iter().map(|(value, &count)| (value, count)).collect()
+  }
+}
+
+impl<U> Default for LazyHeap<U> {
+  fn default() -> Self {
+    LazyHeap::new()
+  }
+}
+
+impl<U: Ord> From<Vec<U>> for LazyHeap<U> {
+  fn from(values: Vec<U>) -> Self {
+    LazyHeap::from(BinaryHeap::from(values))
+  }
+}
+
+impl<U> From<BinaryHeap<U>> for LazyHeap<U> {
+  fn from(heap: BinaryHeap<U>) -> Self {
+    LazyHeap {
+      heap,
+      removed: BTreeMap::new(),
+      removed_len: 0,
+    }
+  }
+}
+
+impl<U: Ord> FromIterator<U> for LazyHeap<U> {
+  fn from_iter<I: IntoIterator<Item = U>>(iter: I) -> Self {
+    LazyHeap::from(iter.into_iter().collect::<BinaryHeap<U>>())
+  }
+}
+
+impl<U: Clone> Clone for LazyHeap<U> {
+  fn clone(&self) -> Self {
+    LazyHeap {
+      heap: self.heap.clone(),
+      removed: self.removed.clone(),
+      removed_len: self.removed_len,
+    }
+  }
+
+  fn clone_from(&mut self, source: &Self) {
+    self.heap.clone_from(&source.heap);
+    self.removed.clone_from(&source.removed);
+    self.removed_len = source.removed_len;
+  }
+}
+
+impl<U: Ord + Debug> Debug for LazyHeap<U> {
+  /// Lists the live values in heap order.
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_list().entries(self.iter()).finish()
+  }
+}
@@ -1,9 +1,70 @@
 //! A library to keep track of a running median of a sequence of numbers.
 
+#![cfg_attr(not(feature = "std"), no_std)]
+
 pub mod mergemedian;
+#[cfg(feature = "std")]
 pub mod medianheap;
-pub use mergemedian::{MergeMedian, LeftHandedMedian, MidpointMedian};
-pub use medianheap::MedianHeap;
+#[cfg(feature = "std")]
+pub mod approx;
+pub mod error;
+pub mod eviction;
+#[cfg(feature = "std")]
+pub mod timeweighted;
+#[cfg(feature = "std")]
+pub mod timewindow;
+#[cfg(feature = "std")]
+pub mod ordered;
+pub mod partial;
+#[cfg(feature = "std")]
+pub mod float;
+#[cfg(feature = "std")]
+pub mod builder;
+#[cfg(feature = "std")]
+pub mod snapshot;
+pub mod staticheap;
+#[cfg(feature = "std")]
+pub mod iterext;
+#[cfg(feature = "std")]
+pub mod decayed;
+#[cfg(feature = "std")]
+pub mod summary;
+pub mod capacity;
+pub mod half;
+#[cfg(feature = "std")]
+mod lazyheap;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[allow(deprecated)]
+pub use mergemedian::{MergeMedian, LeftHandedMedian, RightHandedMedian, MidpointMedian, IntegerMidpointMedian, FloatMidpointMedian, SaturatingMidpointMedian};
+#[cfg(feature = "std")]
+pub use medianheap::{MedianHeap, median_of, rolling_median};
+#[cfg(feature = "std")]
+pub use approx::ApproxMedianHeap;
+pub use error::{FromPartsError, MedianHeapError};
+pub use eviction::EvictionPolicy;
+#[cfg(feature = "std")]
+pub use timeweighted::TimeWeightedMedianHeap;
+#[cfg(feature = "std")]
+pub use timewindow::TimeWindowMedianHeap;
+#[cfg(feature = "std")]
+pub use ordered::OrderedMedianHeap;
+pub use partial::AssertOrd;
+#[cfg(feature = "std")]
+pub use float::FloatMedianHeap;
+#[cfg(feature = "std")]
+pub use builder::MedianHeapBuilder;
+#[cfg(feature = "std")]
+pub use snapshot::MedianSnapshot;
+pub use staticheap::StaticMedianHeap;
+#[cfg(feature = "std")]
+pub use iterext::IteratorExt;
+#[cfg(feature = "std")]
+pub use decayed::DecayedMedianHeap;
+#[cfg(feature = "std")]
+pub use summary::MedianSummary;
+pub use capacity::AutoCapacity;
+pub use half::Half;
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod tests;
\ No newline at end of file
@@ -1,10 +1,16 @@
-use std::collections::BinaryHeap;
-use std::cmp::Reverse;
-use std::fmt::Debug;
-use std::ops::Add;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BinaryHeap, VecDeque};
+use std::cmp::{Ordering, Reverse};
+use std::fmt::{Debug, Display};
+use std::io::{self, BufRead};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::ops::{RangeBounds, Sub};
+use std::str::FromStr;
+use num::{CheckedSub, NumCast, ToPrimitive};
 // use std::vec::IntoIter;
 
-use crate::MergeMedian;
+use crate::lazyheap::LazyHeap;
+use crate::{AutoCapacity, DecayedMedianHeap, EvictionPolicy, FromPartsError, Half, MedianHeapBuilder, MedianSnapshot, MedianSummary, MergeMedian, MedianHeapError};
 
 /// MedianHeap is a struct that represents a heap data structure that can calculate the median of the values in the heap in constant time.
 /// It uses two binary heaps to store the values in the heap: a max heap and a min heap.
@@ -16,9 +22,9 @@ use crate::MergeMedian;
 /// 
 /// Example:
 /// ```
-/// use median_heap::{MidpointMedian, MedianHeap};
+/// use median_heap::{IntegerMidpointMedian, MedianHeap};
 /// 
-/// let mut heap = MedianHeap::new(MidpointMedian);
+/// let mut heap = MedianHeap::new(IntegerMidpointMedian);
 /// heap.push(2);
 /// heap.push(4);
 /// heap.push(6);
@@ -27,18 +33,241 @@ use crate::MergeMedian;
 /// 
 /// assert_eq!(6, heap.get_median().unwrap()); // The median of the values 2, 4, 6, 8, 10 is 6.
 /// ```
+/// 
+/// The heap works for any total order, so wrapping the values in std::cmp::Reverse gives a heap with descending semantics.
+/// The max heap then stores the larger half of the values and LeftHandedMedian picks the larger of two median candidates, which is what RightHandedMedian picks for the unwrapped values.
+/// 
+/// Example:
+/// ```
+/// use std::cmp::Reverse;
+/// use median_heap::{LeftHandedMedian, MedianHeap, RightHandedMedian};
+/// 
+/// let mut descending = MedianHeap::new(LeftHandedMedian);
+/// let mut ascending = MedianHeap::new(RightHandedMedian);
+/// for value in [1, 2, 3, 4] {
+///   descending.push(Reverse(value));
+///   ascending.push(value);
+/// }
+/// 
+/// assert_eq!(Reverse(3), descending.get_median().unwrap());
+/// assert_eq!(3, ascending.get_median().unwrap());
+/// ```
 pub struct MedianHeap<T, K> {
   median_kind: K,
-  max_heap: BinaryHeap<T>,
-  min_heap: BinaryHeap<Reverse<T>>,
+  max_heap: LazyHeap<T>,
+  min_heap: LazyHeap<Reverse<T>>,
+  bound: Option<Bound<T>>,
+  // The smallest and largest values in the heap, None if the heap is empty or the extreme has to be recomputed.
+  // A forgotten extreme is recomputed by the next push, so min and max are O(1) for a push-only workload.
+  cached_min: Option<T>,
+  cached_max: Option<T>,
+  moments: Option<Moments<T>>,
+  duplicates: Option<Duplicates<T>>,
+  // The median observed by the last call to median_delta, None if it wasn't called since the heap was created, cleared or drained.
+  last_median: Option<T>,
+  // How many values the max heap may hold more than the min heap, see with_slack.
+  // The min heap may hold up to slack - 1 values more than the max heap, so the default of 1 keeps the strict invariant.
+  slack: usize,
+  auto_capacity: Option<CapacityTracker>,
+  // Called with the new median by every method that changes the median, see on_median_change.
+  on_median_change: Option<MedianObserver<T, K>>,
+}
+
+// The callback set with on_median_change and the function computing the median it's called with.
+// Capturing get_median when the callback is set lets methods without the Copy and MergeMedian bounds, like pop_min, notify it too.
+// The callback is only called through &mut self, so it's reached with Mutex::get_mut without locking, and the mutex keeps the heap Sync without requiring a Sync callback.
+struct MedianObserver<T, K> {
+  callback: Mutex<MedianCallback<T>>,
+  median: fn(&MedianHeap<T, K>) -> Option<T>,
+}
+
+type MedianCallback<T> = Box<dyn FnMut(&T) + Send>;
+
+// The configuration and usage statistics of a heap that manages its own capacity, see with_auto_capacity.
+#[derive(Clone)]
+struct CapacityTracker {
+  config: AutoCapacity,
+  // The number of consecutive operations that left the heap below config.shrink_below of its capacity.
+  low_operations: usize,
+  // The largest length since the last shrink.
+  high_water: usize,
+  // The largest length before the heap shrank, which it grows back to once it fills up, or 0 if it already did.
+  previous_high_water: usize,
+}
+
+// The capacity and eviction policy of a bounded heap.
+#[derive(Clone)]
+struct Bound<T> {
+  capacity: usize,
+  policy: EvictionPolicy,
+  // The pushed values in chronological order, only kept for EvictOldest.
+  // Values removed by anything other than an eviction stay in here until they're skipped or compacted away.
+  history: VecDeque<T>,
+  // The number of copies of every value that were removed since they were pushed, only kept for EvictOldest.
+  // Equal values are interchangeable, so every removed copy makes the oldest history entry of its value stale.
+  stale: BTreeMap<T, usize>,
+  // The number of stored copies of every value, only kept for the policies evicting an extreme, which need the next extreme after every eviction.
+  values: BTreeMap<T, usize>,
+  // Copies a value into a key of stale, set once values are added.
+  // Values only enter a bounded heap through methods requiring T: Copy, so methods without the bound, like pop_min, can still record a removal.
+  copy: Option<CopyValue<T>>,
+  // Compares the distance from the minimum to the median with the distance from the median to the maximum.
+  // Only set for heaps created with with_farthest_eviction, since measuring distances requires subtraction.
+  compare_distances: Option<DistanceComparator<T>>,
+}
+
+pub(crate) type DistanceComparator<T> = fn(&T, &T, &T) -> Ordering;
+
+impl<T> Bound<T> {
+  fn new(capacity: usize, policy: EvictionPolicy, compare_distances: Option<DistanceComparator<T>>) -> Self {
+    Bound {
+      capacity,
+      policy,
+      history: VecDeque::new(),
+      stale: BTreeMap::new(),
+      values: BTreeMap::new(),
+      copy: None,
+      compare_distances,
+    }
+  }
+
+  // Returns true if the policy evicts an extreme, so the stored values are counted.
+  fn counts_values(&self) -> bool {
+    self.policy != EvictionPolicy::EvictOldest
+  }
+
+  fn clear(&mut self) {
+    self.history.clear();
+    self.stale.clear();
+    self.values.clear();
+  }
+}
+
+impl<T: Ord + Copy> Bound<T> {
+  fn add(&mut self, value: T) {
+    self.copy = Some(|x| *x);
+    if self.counts_values() {
+      *self.values.entry(value).or_insert(0) += 1;
+    }
+  }
+}
+
+impl<T: Ord> Bound<T> {
+  fn remove(&mut self, value: &T) {
+    if self.counts_values() {
+      if let Some(count) = self.values.get_mut(value) {
+        *count -= 1;
+        if *count == 0 {
+          self.values.remove(value);
+        }
+      }
+    } else if let Some(count) = self.stale.get_mut(value) {
+      *count += 1;
+    } else if let Some(copy) = self.copy {
+      self.stale.insert(copy(value), 1);
+    }
+  }
+
+  // Removes a stale history entry of the value if there is one, returning false if the entry stands for a stored copy.
+  fn drop_stale(&mut self, value: &T) -> bool {
+    let Some(count) = self.stale.get_mut(value) else {
+      return false
+    };
+    *count -= 1;
+    if *count == 0 {
+      self.stale.remove(value);
+    }
+    true
+  }
+}
+
+// The running mean and variance of the values, kept up to date with Welford's algorithm once tracking is enabled.
+#[derive(Clone)]
+struct Moments<T> {
+  count: usize,
+  mean: f64,
+  // The sum of the squared distances from the mean.
+  m2: f64,
+  to_f64: fn(&T) -> f64,
+}
+
+impl<T> Moments<T> {
+  fn add(&mut self, value: &T) {
+    let x = (self.to_f64)(value);
+    self.count += 1;
+    let delta = x - self.mean;
+    self.mean += delta / self.count as f64;
+    self.m2 += delta * (x - self.mean);
+  }
+
+  // Reverses add, which loses precision when the removed value is far from the mean of the remaining values.
+  fn remove(&mut self, value: &T) {
+    if self.count <= 1 {
+      self.clear();
+      return
+    }
+
+    let x = (self.to_f64)(value);
+    let n = self.count as f64;
+    let mean = (n * self.mean - x) / (n - 1.0);
+    // Rounding errors can push the sum slightly below zero.
+    self.m2 = (self.m2 - (x - self.mean) * (x - mean)).max(0.0);
+    self.mean = mean;
+    self.count -= 1;
+  }
+
+  fn clear(&mut self) {
+    self.count = 0;
+    self.mean = 0.0;
+    self.m2 = 0.0;
+  }
+}
+
+// The number of copies of every distinct value, kept once the number of copies per value is capped.
+#[derive(Clone)]
+struct Duplicates<T> {
+  limit: usize,
+  counts: BTreeMap<T, usize>,
+  // Copies a value into a key of counts, so inserting doesn't require T: Copy wherever a value is added.
+  copy: CopyValue<T>,
+}
+
+pub(crate) type CopyValue<T> = fn(&T) -> T;
+
+impl<T: Ord> Duplicates<T> {
+  fn add(&mut self, value: &T) {
+    *self.counts.entry((self.copy)(value)).or_insert(0) += 1;
+  }
+
+  fn remove(&mut self, value: &T) {
+    if let Some(count) = self.counts.get_mut(value) {
+      *count -= 1;
+      if *count == 0 {
+        self.counts.remove(value);
+      }
+    }
+  }
+
+  fn is_full(&self, value: &T) -> bool {
+    self.counts.get(value).is_some_and(|&count| count >= self.limit)
+  }
 }
 
 impl<T: Ord, K: Default> Default for MedianHeap<T, K> {
   fn default() -> Self {
     MedianHeap {
       median_kind: K::default(),
-      max_heap: BinaryHeap::new(),
-      min_heap: BinaryHeap::new(),
+      max_heap: LazyHeap::new(),
+      min_heap: LazyHeap::new(),
+      bound: None,
+      cached_min: None,
+      cached_max: None,
+      moments: None,
+      duplicates: None,
+      last_median: None,
+      slack: 1,
+      auto_capacity: None,
+      on_median_change: None,
     }
   }
 }
@@ -48,308 +277,3362 @@ impl<T: Ord, K: MergeMedian<T>> MedianHeap<T, K> {
   /// 
   /// Example:
   /// ```
-  /// use median_heap::{MidpointMedian, MedianHeap};
+  /// use median_heap::{IntegerMidpointMedian, MedianHeap};
   /// 
-  /// let mut heap = MedianHeap::new(MidpointMedian);
+  /// let mut heap = MedianHeap::new(IntegerMidpointMedian);
   /// heap.push(1);
   /// heap.push(2);
   /// 
   /// assert_eq!(1, heap.get_median().unwrap());
   /// ```
   /// 
-  /// In this example, a new MedianHeap instance is created with the MidpointMedian median kind.
+  /// In this example, a new MedianHeap instance is created with the IntegerMidpointMedian median kind.
   pub fn new(median_kind: K) -> Self {
     MedianHeap {
       median_kind,
-      max_heap: BinaryHeap::new(),
-      min_heap: BinaryHeap::new(),
+      max_heap: LazyHeap::new(),
+      min_heap: LazyHeap::new(),
+      bound: None,
+      cached_min: None,
+      cached_max: None,
+      moments: None,
+      duplicates: None,
+      last_median: None,
+      slack: 1,
+      auto_capacity: None,
+      on_median_change: None,
+    }
+  }
+
+  /// Creates a new, empty MedianHeap instance using the default median kind.
+  /// This is the same as MedianHeap::default(), but doesn't require importing the Default trait or naming it at the call site.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{IntegerMidpointMedian, MedianHeap};
+  ///
+  /// let mut heap = MedianHeap::<i32, IntegerMidpointMedian>::empty();
+  /// heap.push(1);
+  /// heap.push(3);
+  ///
+  /// assert_eq!(2, heap.get_median().unwrap());
+  /// ```
+  pub fn empty() -> Self
+  where
+    K: Default,
+  {
+    MedianHeap::new(K::default())
+  }
+
+  /// Returns a MedianHeapBuilder using the default median kind, the recommended way to configure a bounded or windowed heap.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, LeftHandedMedian};
+  ///
+  /// let mut heap: MedianHeap<i32, LeftHandedMedian> = MedianHeap::builder().window(2).build();
+  /// heap.push(1);
+  /// heap.push(5);
+  /// heap.push(9);
+  ///
+  /// assert_eq!(5, heap.get_median().unwrap());
+  /// ```
+  pub fn builder() -> MedianHeapBuilder<T, K>
+  where
+    K: Default,
+  {
+    MedianHeapBuilder::default()
+  }
+
+  /// Creates a new MedianHeap instance that holds at most capacity values.
+  /// When a push would exceed the capacity, the specified policy decides which value is evicted.
+  /// See EvictionPolicy for the available policies.
+  ///
+  /// EvictionPolicy::EvictFarthestFromMedian measures distances between values, so it needs a heap created with with_farthest_eviction instead.
+  ///
+  /// A push that evicts a value takes O(logn) amortized, like any other push.
+  /// The evicted value stays in its half until it reaches the root, and the half is rebuilt once such values make up half of it.
+  /// The policies evicting an extreme also count the stored values in an ordered map to find the next extreme, which EvictOldest doesn't need.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{EvictionPolicy, MedianHeap, IntegerMidpointMedian};
+  ///
+  /// let mut heap = MedianHeap::with_policy(IntegerMidpointMedian, 3, EvictionPolicy::EvictExtreme);
+  /// heap.push(5);
+  /// heap.push(6);
+  /// heap.push(7);
+  /// heap.push(100);
+  ///
+  /// assert_eq!(3, heap.len());
+  /// assert!(!heap.contains(&100));
+  /// ```
+  ///
+  /// # Panics
+  /// Panics if capacity is 0 or policy is EvictionPolicy::EvictFarthestFromMedian.
+  pub fn with_policy(median_kind: K, capacity: usize, policy: EvictionPolicy) -> Self {
+    MedianHeap::bounded(median_kind, capacity, policy, None)
+  }
+
+  /// Creates a new MedianHeap instance that holds at most capacity values and evicts with EvictionPolicy::EvictFarthestFromMedian.
+  /// When a push would exceed the capacity, the smallest or the largest value is evicted, whichever is farther from the median.
+  /// Measuring the distances subtracts values, which is why this policy has its own constructor.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, IntegerMidpointMedian};
+  ///
+  /// let mut heap = MedianHeap::with_farthest_eviction(IntegerMidpointMedian, 3);
+  /// heap.push(5);
+  /// heap.push(6);
+  /// heap.push(7);
+  /// heap.push(100);
+  ///
+  /// assert_eq!(3, heap.len());
+  /// assert!(!heap.contains(&100));
+  /// ```
+  ///
+  /// # Panics
+  /// Panics if capacity is 0.
+  pub fn with_farthest_eviction(median_kind: K, capacity: usize) -> Self
+  where
+    T: Copy + Sub<Output = T>,
+  {
+    MedianHeap::bounded(median_kind, capacity, EvictionPolicy::EvictFarthestFromMedian, Some(compare_distances))
+  }
+
+  // Creates a bounded heap, shared by the constructors and the builder.
+  pub(crate) fn bounded(median_kind: K, capacity: usize, policy: EvictionPolicy, compare_distances: Option<DistanceComparator<T>>) -> Self {
+    assert!(capacity > 0, "capacity must be greater than 0");
+    assert!(
+      policy != EvictionPolicy::EvictFarthestFromMedian || compare_distances.is_some(),
+      "EvictFarthestFromMedian subtracts values, create the heap with MedianHeap::with_farthest_eviction or MedianHeapBuilder::evict_farthest_from_median"
+    );
+    let mut heap = MedianHeap::new(median_kind);
+    heap.bound = Some(Bound::new(capacity, policy, compare_distances));
+    heap
+  }
+
+  /// Creates a new DecayedMedianHeap instance, which tracks an exponentially weighted median where the weight of older values decays by lambda on every push.
+  /// The weights don't fit the two heaps of a MedianHeap, so this returns a separate type. See DecayedMedianHeap.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, IntegerMidpointMedian};
+  ///
+  /// let mut heap = MedianHeap::with_decay(IntegerMidpointMedian, 0.9);
+  /// heap.push(10);
+  /// heap.push(20);
+  ///
+  /// assert_eq!(20, heap.get_median().unwrap());
+  /// ```
+  ///
+  /// # Panics
+  /// Panics if lambda isn't in (0, 1] or is subnormal.
+  pub fn with_decay(median_kind: K, lambda: f64) -> DecayedMedianHeap<T, K>
+  where
+    T: Copy,
+  {
+    DecayedMedianHeap::new(median_kind, lambda)
+  }
+
+  /// Creates a new MedianHeap instance that holds at most max_duplicates copies of any single value.
+  /// Pushing a value the heap already holds max_duplicates times is a no-op, which bounds the memory taken by a few values dominating the stream.
+  /// The cap applies to push, push_all, extend and replace_median, while update, which replaces a value in place, doesn't check it.
+  /// This is a shortcut for MedianHeapBuilder::max_duplicates, which combines the cap with the other options.
+  ///
+  /// The copies are counted per distinct value in a separate map, which every removal keeps up to date.
+  /// Removing a copy with pop, delete or any other removal frees up room for a new copy of that value.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, LeftHandedMedian};
+  ///
+  /// let mut heap = MedianHeap::with_max_duplicates(LeftHandedMedian, 2);
+  /// heap.push_all([1, 1, 1, 2, 2, 2, 3]);
+  /// assert_eq!(vec![1, 1, 2, 2, 3], heap.to_sorted_vec());
+  ///
+  /// heap.delete(&1);
+  /// heap.push(1);
+  /// assert_eq!(vec![1, 2, 2, 3], heap.to_sorted_vec());
+  /// ```
+  ///
+  /// # Panics
+  /// Panics if max_duplicates is 0.
+  pub fn with_max_duplicates(median_kind: K, max_duplicates: usize) -> Self
+  where
+    T: Copy,
+  {
+    MedianHeapBuilder::new(median_kind).max_duplicates(max_duplicates).build()
+  }
+
+  // Caps the number of copies of every value of a new heap, used by the builder.
+  pub(crate) fn cap_duplicates(&mut self, max_duplicates: usize, copy: CopyValue<T>) {
+    assert!(max_duplicates > 0, "max_duplicates must be greater than 0");
+    self.duplicates = Some(Duplicates {
+      limit: max_duplicates,
+      counts: BTreeMap::new(),
+      copy,
+    });
+  }
+
+  /// Creates a new MedianHeap instance whose heaps may drift up to slack values apart before they're rebalanced.
+  /// The max heap may hold up to slack values more than the min heap, and the min heap up to slack - 1 values more than the max heap.
+  /// A slack of 1 is the same as MedianHeap::new, where the max heap holds as many values as the min heap or one more.
+  ///
+  /// A larger slack moves fewer values between the heaps when values are added and removed on one side of the median, at the cost of finding the median a few levels below the root of the larger heap.
+  /// The median is found by visiting the values of the larger heap in order from its root, so get_median takes O(klogk) instead of O(1).
+  /// Methods removing the median, like pop, restore the strict balance first.
+  /// This is meant for benchmarking the cost of rebalancing, the default is the better choice otherwise.
+  /// MedianHeapBuilder::slack sets the same slack on a heap configured with other options as well.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, LeftHandedMedian};
+  ///
+  /// let mut heap = MedianHeap::with_slack(LeftHandedMedian, 4);
+  /// heap.push_all([1, 2, 3, 4, 5, 6, 7]);
+  /// assert!(heap.balance() <= 4);
+  ///
+  /// assert_eq!(4, heap.get_median().unwrap());
+  /// assert_eq!(Some(4), heap.pop());
+  /// ```
+  ///
+  /// # Panics
+  /// Panics if slack is 0.
+  pub fn with_slack(median_kind: K, slack: usize) -> Self {
+    MedianHeapBuilder::new(median_kind).slack(slack).build()
+  }
+
+  // Sets the slack of a new heap, used by the builder.
+  pub(crate) fn set_slack(&mut self, slack: usize) {
+    assert!(slack > 0, "slack must be greater than 0");
+    self.slack = slack;
+  }
+
+  /// Creates a new MedianHeap instance that shrinks and grows its capacity on its own according to the specified configuration, see AutoCapacity.
+  /// The capacity is adjusted after every method that adds or removes values, each of which counts as one operation; clear, drain and take leave it as it is.
+  /// Use MedianHeapBuilder::auto_capacity to combine it with a capacity bound or the other options.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{AutoCapacity, MedianHeap, IntegerMidpointMedian};
+  ///
+  /// let mut heap = MedianHeap::with_auto_capacity(IntegerMidpointMedian, AutoCapacity::default());
+  /// heap.push(1);
+  ///
+  /// assert_eq!(1, heap.get_median().unwrap());
+  /// ```
+  ///
+  /// # Panics
+  /// Panics if config.shrink_below isn't greater than 0 and at most 0.5, or if config.shrink_after is 0.
+  pub fn with_auto_capacity(median_kind: K, config: AutoCapacity) -> Self {
+    MedianHeapBuilder::new(median_kind).auto_capacity(config).build()
+  }
+
+  // Lets a new heap manage its own capacity, used by the builder.
+  pub(crate) fn manage_capacity(&mut self, config: AutoCapacity) {
+    assert!(config.shrink_below > 0.0 && config.shrink_below <= 0.5, "shrink_below must be greater than 0 and at most 0.5");
+    assert!(config.shrink_after > 0, "shrink_after must be greater than 0");
+    self.auto_capacity = Some(CapacityTracker {
+      config,
+      low_operations: 0,
+      high_water: 0,
+      previous_high_water: 0,
+    });
+  }
+
+  /// Creates a new MedianHeap instance with the specified median kind from values that are already sorted in ascending order.
+  /// The values are split at the midpoint and both heaps are built directly, without comparing values to the median one by one.
+  ///
+  /// The values must be sorted, otherwise the heap gives incorrect results.
+  /// This is only checked by a debug assertion.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, IntegerMidpointMedian};
+  ///
+  /// let heap = MedianHeap::from_sorted(IntegerMidpointMedian, vec![1, 2, 3, 4, 5, 6]);
+  ///
+  /// assert_eq!(6, heap.len());
+  /// assert_eq!(3, heap.get_median().unwrap());
+  /// ```
+  ///
+  /// # Complexity
+  /// O(n)
+  pub fn from_sorted(median_kind: K, mut sorted: Vec<T>) -> Self {
+    debug_assert!(sorted.is_sorted(), "the values passed to from_sorted must be sorted in ascending order");
+    let upper = sorted.split_off(sorted.len().div_ceil(2));
+    let mut heap = MedianHeap::new(median_kind);
+    heap.max_heap = LazyHeap::from(sorted);
+    heap.min_heap = upper.into_iter().map(Reverse).collect();
+    heap
+  }
+
+  /// Creates a new MedianHeap instance with the specified median kind holding the values of two iterators, like two data sources combined in a map-reduce step.
+  /// The median is the same as the median of the concatenated values, but the values are collected once using both size hints and split at the midpoint by selection, so each heap is built in a single pass instead of value by value.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, IntegerMidpointMedian};
+  ///
+  /// let heap = MedianHeap::from_two(IntegerMidpointMedian, vec![1, 5, 9], [2, 3]);
+  ///
+  /// assert_eq!(5, heap.len());
+  /// assert_eq!(3, heap.get_median().unwrap());
+  /// ```
+  ///
+  /// # Complexity
+  /// O(n)
+  pub fn from_two<I: IntoIterator<Item = T>, J: IntoIterator<Item = T>>(median_kind: K, a: I, b: J) -> Self {
+    let (a, b) = (a.into_iter(), b.into_iter());
+    let mut values = Vec::with_capacity(a.size_hint().0 + b.size_hint().0);
+    values.extend(a);
+    values.extend(b);
+
+    let mut heap = MedianHeap::new(median_kind);
+    let lower_len = values.len().div_ceil(2);
+    if lower_len > 0 {
+      // Every value before the selected one is at most it and every value after it at least it, so the lower half ends with it.
+      values.select_nth_unstable(lower_len - 1);
+    }
+    let upper = values.split_off(lower_len);
+    heap.max_heap = LazyHeap::from(values);
+    heap.min_heap = upper.into_iter().map(Reverse).collect();
+    heap
+  }
+
+  /// Deconstructs the heap into its median kind, its max heap holding the lower half of the values and its min heap holding the upper half.
+  /// Together with from_parts, this moves a heap in and out of other representations without pushing every value again.
+  /// The capacity, eviction policy and tracked variance of the heap are dropped.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, IntegerMidpointMedian};
+  ///
+  /// let heap = MedianHeap::from_sorted(IntegerMidpointMedian, vec![1, 2, 3]);
+  /// let (kind, max_heap, min_heap) = heap.into_parts();
+  ///
+  /// assert_eq!(vec![1, 2], max_heap.clone().into_sorted_vec());
+  /// assert_eq!(1, min_heap.len());
+  ///
+  /// let heap = MedianHeap::from_parts(kind, max_heap, min_heap);
+  /// assert_eq!(2, heap.get_median().unwrap());
+  /// ```
+  ///
+  /// # Complexity
+  /// O(1)
+  pub fn into_parts(self) -> (K, BinaryHeap<T>, BinaryHeap<Reverse<T>>) {
+    (self.median_kind, self.max_heap.into_inner(), self.min_heap.into_inner())
+  }
+
+  /// Creates a new MedianHeap instance from a median kind, a max heap holding the lower half of the values and a min heap holding the upper half.
+  /// The halves may differ in length by one value in either direction, the heap moves a root to restore its own balance.
+  ///
+  /// The halves aren't validated, so every value of the max heap must be at most every value of the min heap, otherwise the heap gives incorrect results.
+  /// Use try_from_parts to validate the halves.
+  ///
+  /// # Complexity
+  /// O(logn)
+  pub fn from_parts(median_kind: K, max_heap: BinaryHeap<T>, min_heap: BinaryHeap<Reverse<T>>) -> Self {
+    let mut heap = MedianHeap::new(median_kind);
+    heap.max_heap = LazyHeap::from(max_heap);
+    heap.min_heap = LazyHeap::from(min_heap);
+    heap.rebalance();
+    heap
+  }
+
+  /// Creates a new MedianHeap instance from its parts in the same way as from_parts, after validating them.
+  /// If the halves differ in length by more than one value, the method returns FromPartsError::Unbalanced with both lengths.
+  /// If the root of the max heap is greater than the root of the min heap, the method returns FromPartsError::Misordered with both roots.
+  ///
+  /// Example:
+  /// ```
+  /// use std::cmp::Reverse;
+  /// use std::collections::BinaryHeap;
+  /// use median_heap::{FromPartsError, MedianHeap, IntegerMidpointMedian};
+  ///
+  /// let max_heap = BinaryHeap::from(vec![1, 2]);
+  /// let min_heap = BinaryHeap::from(vec![Reverse(3), Reverse(4), Reverse(5)]);
+  /// let heap = MedianHeap::try_from_parts(IntegerMidpointMedian, max_heap, min_heap).unwrap();
+  /// assert_eq!(3, heap.get_median().unwrap());
+  ///
+  /// let max_heap = BinaryHeap::from(vec![1, 4]);
+  /// let min_heap = BinaryHeap::from(vec![Reverse(3)]);
+  /// let result = MedianHeap::try_from_parts(IntegerMidpointMedian, max_heap, min_heap);
+  /// assert_eq!(Some(FromPartsError::Misordered { max_root: 4, min_root: 3 }), result.err());
+  /// ```
+  ///
+  /// # Complexity
+  /// O(logn)
+  pub fn try_from_parts(median_kind: K, max_heap: BinaryHeap<T>, min_heap: BinaryHeap<Reverse<T>>) -> Result<Self, FromPartsError<T>>
+  where
+    T: Clone,
+  {
+    if max_heap.len().abs_diff(min_heap.len()) > 1 {
+      return Err(FromPartsError::Unbalanced { max_len: max_heap.len(), min_len: min_heap.len() })
+    }
+    if let (Some(left), Some(Reverse(right))) = (max_heap.peek(), min_heap.peek()) {
+      if left > right {
+        return Err(FromPartsError::Misordered { max_root: left.clone(), min_root: right.clone() })
+      }
+    }
+    Ok(MedianHeap::from_parts(median_kind, max_heap, min_heap))
+  }
+
+  /// Moves the values out of the heap into a new heap and returns it, leaving self empty, like std::mem::take.
+  /// The values aren't cloned, so this is a cheap way to hand the accumulated values to another owner while self stays usable.
+  ///
+  /// Both heaps get a clone of the median kind.
+  /// The returned heap keeps the capacity, eviction policy, duplicate cap and variance tracking of self, and self keeps the same configuration, starting over empty.
+  /// The callback registered with on_median_change isn't moved: it stays on self, and the returned heap has none.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{EvictionPolicy, MedianHeap, IntegerMidpointMedian};
+  ///
+  /// let mut heap = MedianHeap::with_policy(IntegerMidpointMedian, 2, EvictionPolicy::EvictOldest);
+  /// heap.push_all([1, 2, 3]);
+  ///
+  /// let taken = heap.take();
+  /// assert_eq!(vec![2, 3], taken.to_sorted_vec());
+  /// assert!(heap.is_empty());
+  ///
+  /// heap.push_all([4, 5, 6]);
+  /// assert_eq!(vec![5, 6], heap.to_sorted_vec());
+  /// ```
+  ///
+  /// # Complexity
+  /// O(1)
+  pub fn take(&mut self) -> Self
+  where
+    K: Clone,
+  {
+    let mut empty = MedianHeap::new(self.median_kind.clone());
+    empty.bound = self.bound.as_ref().map(|bound| Bound::new(bound.capacity, bound.policy, bound.compare_distances));
+    empty.moments = self.moments.as_ref().map(|moments| Moments {
+      count: 0,
+      mean: 0.0,
+      m2: 0.0,
+      to_f64: moments.to_f64,
+    });
+    empty.duplicates = self.duplicates.as_ref().map(|duplicates| Duplicates {
+      limit: duplicates.limit,
+      counts: BTreeMap::new(),
+      copy: duplicates.copy,
+    });
+    empty.slack = self.slack;
+    empty.auto_capacity = self.auto_capacity.as_ref().map(|tracker| CapacityTracker {
+      config: tracker.config,
+      low_operations: 0,
+      high_water: 0,
+      previous_high_water: 0,
+    });
+    empty.on_median_change = self.on_median_change.take();
+    std::mem::replace(self, empty)
+  }
+}
+
+impl<T: Ord + Copy, K: MergeMedian<T>> MedianHeap<T, K> {
+  /// Returns the median of the values in the heap.
+  /// If the heap is empty, the method returns None.
+  /// 
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, LeftHandedMedian};
+  /// 
+  /// let mut heap = MedianHeap::new(LeftHandedMedian);
+  /// heap.push(1);
+  /// heap.push(2);
+  /// heap.push(3);
+  /// heap.push(4);
+  /// 
+  /// assert_eq!(2, heap.get_median().unwrap());
+  /// ```
+  /// 
+  /// In this example, the median of the values 1, 2, 3, 4 is 2.
+  /// 
+  /// # Complexity
+  /// O(1), O(klogk) for a heap created with a slack of k
+  pub fn get_median(&self) -> Option<T> {
+    // If the number of values is even, two candidates are found.
+    // If not then both candidates are the middle value, which is the median.
+    let (left, right) = self.median_candidate_refs()?;
+    if self.len().is_multiple_of(2) {
+      // Merge the two candidates to get the median.
+      Some(self.median_kind.merge(left, right))
+    } else {
+      Some(*left)
+    }
+  }
+
+  /// Returns whether the median reported by get_median is one of the values stored in the heap.
+  /// If the heap is empty, the method returns None.
+  ///
+  /// When the number of values is odd, the median is the middle value, so it's always present.
+  /// When it's even, the median kind merges the two middle values, which can produce a value that isn't stored, like IntegerMidpointMedian reporting 3 for 2 and 4.
+  /// Such a synthetic median can't be deleted and doesn't correspond to a real data point.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, IntegerMidpointMedian};
+  ///
+  /// let mut heap = MedianHeap::new(IntegerMidpointMedian);
+  /// assert_eq!(None, heap.median_is_present());
+  ///
+  /// heap.push(2);
+  /// heap.push(4);
+  /// assert_eq!(Some(false), heap.median_is_present());
+  ///
+  /// heap.push(3);
+  /// assert_eq!(Some(true), heap.median_is_present());
+  ///
+  /// heap.push(3);
+  /// assert_eq!(Some(true), heap.median_is_present());
+  /// ```
+  ///
+  /// # Complexity
+  /// O(1) if the median is a middle value or lies strictly between them, O(n) otherwise
+  pub fn median_is_present(&self) -> Option<bool> {
+    let median = self.get_median()?;
+    if !self.len().is_multiple_of(2) {
+      return Some(true)
+    }
+
+    let (&left, &right) = self.median_candidate_refs().unwrap();
+    if median == left || median == right {
+      Some(true)
+    } else if left < median && median < right {
+      // No value lies strictly between the two middle values.
+      Some(false)
+    } else {
+      // A custom median kind can merge the candidates into a value outside of them.
+      Some(self.contains(&median))
+    }
+  }
+
+  /// Compares the median of this heap with the median of the other heap, using the Ord implementation of T.
+  /// If either heap is empty, the method returns None.
+  ///
+  /// Example:
+  /// ```
+  /// use std::cmp::Ordering;
+  /// use median_heap::{MedianHeap, IntegerMidpointMedian};
+  ///
+  /// let first: MedianHeap<i32, IntegerMidpointMedian> = vec![1, 2, 3].into_iter().collect();
+  /// let second: MedianHeap<i32, IntegerMidpointMedian> = vec![2, 4].into_iter().collect();
+  ///
+  /// assert_eq!(Some(Ordering::Less), first.cmp_median(&second));
+  /// assert_eq!(None, first.cmp_median(&MedianHeap::empty()));
+  /// ```
+  ///
+  /// # Complexity
+  /// O(1)
+  pub fn cmp_median(&self, other: &Self) -> Option<Ordering> {
+    Some(self.get_median()?.cmp(&other.get_median()?))
+  }
+
+  /// Orders this heap relative to the other heap by median, then by number of values.
+  /// Empty heaps sort before every other heap, and heaps with equal medians sort by len.
+  /// Unlike cmp_median, the result is a total order, so it can be passed to sort_by to sort a collection of heaps.
+  ///
+  /// MedianHeap doesn't implement Ord itself, since two heaps ordering as equal here can still hold different values.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, LeftHandedMedian};
+  ///
+  /// let mut heaps: Vec<MedianHeap<i32, LeftHandedMedian>> = vec![
+  ///   vec![5, 6, 7].into_iter().collect(),
+  ///   vec![1, 6, 9].into_iter().collect(),
+  ///   MedianHeap::empty(),
+  ///   vec![2].into_iter().collect(),
+  /// ];
+  /// heaps.sort_by(|a, b| a.cmp_by_median(b));
+  ///
+  /// let medians: Vec<Option<i32>> = heaps.iter().map(|heap| heap.get_median()).collect();
+  /// assert_eq!(vec![None, Some(2), Some(6), Some(6)], medians);
+  /// ```
+  ///
+  /// # Complexity
+  /// O(1)
+  pub fn cmp_by_median(&self, other: &Self) -> Ordering {
+    self.get_median().cmp(&other.get_median()).then(self.len().cmp(&other.len()))
+  }
+
+  /// Returns a snapshot of the median, length, extremes and quartiles of the heap, which can be shared between threads.
+  /// See MedianSnapshot.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, LeftHandedMedian};
+  ///
+  /// let mut heap: MedianHeap<i32, LeftHandedMedian> = vec![1, 2, 3].into_iter().collect();
+  /// let snapshot = heap.snapshot();
+  /// heap.clear();
+  ///
+  /// assert_eq!(Some(2), snapshot.median);
+  /// assert_eq!(3, snapshot.len);
+  /// ```
+  ///
+  /// # Complexity
+  /// O(n)
+  pub fn snapshot(&self) -> Arc<MedianSnapshot<T>> {
+    Arc::new(MedianSnapshot::new(self))
+  }
+
+  /// Returns the median of the values in the heap, or the specified default if the heap is empty.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, LeftHandedMedian};
+  ///
+  /// let mut heap = MedianHeap::new(LeftHandedMedian);
+  /// assert_eq!(42, heap.get_median_or(42));
+  ///
+  /// heap.push(1);
+  /// assert_eq!(1, heap.get_median_or(42));
+  /// ```
+  ///
+  /// # Complexity
+  /// O(1)
+  pub fn get_median_or(&self, default: T) -> T {
+    self.get_median().unwrap_or(default)
+  }
+
+  /// Returns the median of the values in the heap, or the default value of T if the heap is empty.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, LeftHandedMedian};
+  ///
+  /// let mut heap = MedianHeap::new(LeftHandedMedian);
+  /// assert_eq!(0, heap.get_median_or_default());
+  ///
+  /// heap.push(1);
+  /// assert_eq!(1, heap.get_median_or_default());
+  /// ```
+  ///
+  /// # Complexity
+  /// O(1)
+  pub fn get_median_or_default(&self) -> T
+  where
+    T: Default,
+  {
+    self.get_median().unwrap_or_default()
+  }
+
+  /// Returns the median of the values in the heap, using the specified closure instead of the median kind to merge two median candidates.
+  /// The closure is only used for this call, nothing is stored or modified.
+  /// If the heap is empty, the method returns None.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, LeftHandedMedian};
+  ///
+  /// let mut heap = MedianHeap::new(LeftHandedMedian);
+  /// heap.push(1);
+  /// heap.push(4);
+  ///
+  /// assert_eq!(1, heap.get_median().unwrap());
+  /// assert_eq!(Some(4), heap.median_with(|_, b| *b));
+  /// ```
+  ///
+  /// # Complexity
+  /// O(1)
+  pub fn median_with<F: Fn(&T, &T) -> T>(&self, f: F) -> Option<T> {
+    self.median_with_strategy(|x| *x, f)
+  }
+}
+
+impl<T: Ord + Copy, K: MergeMedian<T>> MedianHeap<T, K> {
+  /// Sets a callback that's called with the new median whenever a method adding or removing values changes the median, replacing any previous callback.
+  /// This lets event-driven code react to a changing median instead of polling get_median.
+  ///
+  /// The callback runs synchronously within the mutating call, after the heap has been updated, so a slow callback slows down every change.
+  /// Every method calls it at most once, if the median after the call differs from the median before it, so batched methods like push_all or replace_median don't report intermediate medians.
+  /// It isn't called when the heap becomes empty, nor by clear, drain, take and set_median_kind.
+  /// A clone of the heap doesn't keep the callback, since a boxed closure can't be cloned, and take leaves it on the original heap.
+  ///
+  /// The callback has to be Send so the heap stays Send and can still be moved to another thread, which rules out callbacks capturing an Rc.
+  /// It doesn't have to be Sync, since it's only called by methods taking &mut self.
+  ///
+  /// Example:
+  /// ```
+  /// use std::sync::{Arc, Mutex};
+  /// use median_heap::{MedianHeap, LeftHandedMedian};
+  ///
+  /// let changes = Arc::new(Mutex::new(Vec::new()));
+  /// let mut heap = MedianHeap::new(LeftHandedMedian);
+  /// let observed = Arc::clone(&changes);
+  /// heap.on_median_change(move |median| observed.lock().unwrap().push(*median));
+  ///
+  /// heap.push(5);
+  /// heap.push(7);
+  /// heap.push(6);
+  /// heap.pop();
+  ///
+  /// // Pushing 7 leaves 5 as the median, so it isn't reported.
+  /// assert_eq!(vec![5, 6, 5], *changes.lock().unwrap());
+  /// ```
+  pub fn on_median_change(&mut self, f: impl FnMut(&T) + Send + 'static) {
+    self.on_median_change = Some(MedianObserver {
+      callback: Mutex::new(Box::new(f)),
+      median: MedianHeap::get_median,
+    });
+  }
+
+  /// Adds a value to the heap.
+  /// 
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, LeftHandedMedian};
+  /// 
+  /// let mut heap = MedianHeap::new(LeftHandedMedian);
+  /// heap.push(2);
+  /// 
+  /// assert_eq!(2, heap.get_median().unwrap());
+  /// 
+  /// heap.push(1);
+  /// 
+  /// assert_eq!(1, heap.get_median().unwrap());
+  /// ```
+  pub fn push(&mut self, value: T) {
+    if self.duplicates.as_ref().is_some_and(|duplicates| duplicates.is_full(&value)) {
+      return
+    }
+    let observed = self.observed_median();
+
+    // Only the eviction policies need to know on which side of the median the value was added.
+    let above_median = self.bound.is_some() && self.get_median().is_some_and(|median| value > median);
+
+    // Compare the value against the roots, the largest value of the lower half and the smallest value of the upper half.
+    // A value below the root of the max heap belongs in the max heap and a value above the root of the min heap in the min heap.
+    // A value between or equal to the roots fits in either, so it goes to the heap that keeps the sizes balanced, which means a constant stream never has to be rebalanced.
+    // Comparing against stored values instead of the merged median avoids a merge per push and isn't affected by how the median kind rounds.
+    let below_lower = self.max_heap.peek().is_some_and(|root| value < *root);
+    let above_upper = self.min_heap.peek().is_some_and(|root| value > root.0);
+    if below_lower || (!above_upper && self.max_heap.len() <= self.min_heap.len()) {
+      self.max_heap.push(value);
+    } else {
+      self.min_heap.push(Reverse(value));
+    }
+
+    // Balance the heaps.
+    // If the difference between the number of values in the max heap and min heap is greater than 1, pop the root of the larger heap and push it to the smaller heap.
+    // This ensures that the difference between the number of values in the max heap and min heap is at most 1.
+    self.rebalance();
+    self.track_added(&value);
+    self.cache_pushed(value);
+
+    if self.bound.is_some() {
+      self.evict(value, above_median);
+    }
+    self.finish_change(observed);
+  }
+
+  // Adds a pushed value to the cached extremes, recomputing an extreme that was forgotten.
+  // A heap bounded with EvictOldest would have to scan its values, so it leaves the extreme forgotten for min and max to compute, which keeps its pushes O(logn).
+  fn cache_pushed(&mut self, value: T) {
+    let recompute = self.bound.as_ref().is_none_or(|bound| bound.counts_values());
+    let min = match self.cached_min {
+      Some(min) => Some(min.min(value)),
+      None if recompute => self.min().copied(),
+      None => None,
+    };
+    let max = match self.cached_max {
+      Some(max) => Some(max.max(value)),
+      None if recompute => self.max().copied(),
+      None => None,
+    };
+    self.cached_min = min;
+    self.cached_max = max;
+  }
+
+  // Updates the cached extremes after values were only removed from the roots of the heaps.
+  // Removing a root equal to an extreme means the whole half holds that value, so the extreme only changes once a half has at most one value left.
+  fn cache_roots_removed(&mut self) {
+    if self.max_heap.len() <= 1 {
+      self.cached_min = self.max_heap.peek().copied().or(self.min_heap.peek().map(|x| x.0));
+    }
+    if self.min_heap.len() <= 1 {
+      self.cached_max = self.min_heap.peek().map(|x| x.0).or(self.max_heap.peek().copied());
+    }
+  }
+
+  // Records an added value in the tracked variance, the duplicate counts and the stored values of a bounded heap.
+  fn track_added(&mut self, value: &T) {
+    if let Some(moments) = self.moments.as_mut() {
+      moments.add(value);
+    }
+    if let Some(duplicates) = self.duplicates.as_mut() {
+      duplicates.add(value);
+    }
+    if let Some(bound) = self.bound.as_mut() {
+      bound.add(*value);
+    }
+  }
+
+  // Records the pushed value for a bounded heap and evicts a value according to its policy if the capacity is exceeded.
+  fn evict(&mut self, value: T, above_median: bool) {
+    let bound = self.bound.as_mut().unwrap();
+    let (capacity, policy) = (bound.capacity, bound.policy);
+    if policy == EvictionPolicy::EvictOldest {
+      bound.history.push_back(value);
+    }
+
+    let history_len = bound.history.len();
+    if self.len() <= capacity {
+      // Drop history entries of values that were removed by other methods, so it can't grow without bound.
+      if history_len > 2 * capacity {
+        self.compact_history();
+      }
+      return
+    }
+
+    let evicted = match policy {
+      EvictionPolicy::EvictOldest => self.oldest(),
+      EvictionPolicy::EvictFarthestFromMedian => self.farthest_from_median(),
+      EvictionPolicy::EvictExtreme if above_median => *self.max().unwrap(),
+      EvictionPolicy::EvictExtreme => *self.min().unwrap(),
+    };
+    self.remove_evicted(evicted);
+  }
+
+  // Returns the smallest or the largest value of a bounded heap, whichever is farther from the median.
+  // Without a way to measure distances, it returns the extreme of the larger half, which keeps the values around the median.
+  fn farthest_from_median(&self) -> T {
+    let (min, max) = (*self.min().unwrap(), *self.max().unwrap());
+    let closer_to_min = match self.bound.as_ref().unwrap().compare_distances {
+      Some(compare_distances) => compare_distances(&min, &self.get_median().unwrap(), &max) == Ordering::Less,
+      None => self.max_heap.len() <= self.min_heap.len(),
+    };
+    if closer_to_min {
+      max
+    } else {
+      min
+    }
+  }
+
+  // Removes a value picked for eviction and rebalances the heap.
+  // The value is removed lazily from the half holding it, so this takes O(logn) amortized.
+  fn remove_evicted(&mut self, evicted: T) {
+    if self.max_heap.peek().is_some_and(|root| evicted <= *root) {
+      self.max_heap.remove(evicted);
+    } else {
+      self.min_heap.remove(Reverse(evicted));
+    }
+    self.rebalance();
+    self.track_removed(&evicted);
+    self.forget_extreme(&evicted);
+  }
+
+  /// Changes the capacity of a bounded heap at runtime, like the size of a moving-median window.
+  /// Growing the window only raises the cap, the heap fills up with the following pushes.
+  /// Shrinking the window evicts values until the heap holds at most new_window values, just like a push exceeding the capacity does.
+  /// A heap bounded with EvictionPolicy::EvictOldest drops its oldest values, so the window keeps the most recent ones.
+  /// EvictionPolicy::EvictExtreme has no pushed value to pick a side of the median, so it evicts the extreme of the larger half, which keeps the values around the median.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, LeftHandedMedian};
+  ///
+  /// let mut heap: MedianHeap<i32, LeftHandedMedian> = MedianHeap::builder().window(5).build();
+  /// heap.push_all([1, 2, 3, 4, 5]);
+  ///
+  /// heap.set_window(2);
+  /// assert_eq!(vec![4, 5], heap.to_sorted_vec());
+  ///
+  /// heap.set_window(3);
+  /// heap.push(6);
+  /// assert_eq!(vec![4, 5, 6], heap.to_sorted_vec());
+  /// ```
+  ///
+  /// # Panics
+  /// Panics if the heap wasn't created with a capacity, or if new_window is 0.
+  ///
+  /// # Complexity
+  /// O(mlogn) amortized for m evicted values, O(1) when growing
+  pub fn set_window(&mut self, new_window: usize) {
+    assert!(new_window > 0, "window must be greater than 0");
+    let bound = self.bound.as_mut().expect("set_window requires a heap created with a capacity");
+    bound.capacity = new_window;
+    let policy = bound.policy;
+
+    let observed = self.observed_median();
+    while self.len() > new_window {
+      let evicted = match policy {
+        EvictionPolicy::EvictOldest => self.oldest(),
+        _ => self.farthest_from_median(),
+      };
+      self.remove_evicted(evicted);
+    }
+    self.finish_change(observed);
+  }
+
+  // Returns the oldest value that is still stored in the heap, dropping the stale history entries in front of it.
+  // Its entry stays at the front, since evicting the value marks it stale like any other removal, so the next call drops it.
+  //
+  // # Complexity
+  // O(logn) amortized, since every stale entry is dropped once
+  fn oldest(&mut self) -> T {
+    let bound = self.bound.as_mut().unwrap();
+    loop {
+      let oldest = *bound.history.front().unwrap();
+      if !bound.drop_stale(&oldest) {
+        return oldest
+      }
+      bound.history.pop_front();
+    }
+  }
+
+  // Drops the stale history entries, which are the oldest entries of the removed values.
+  fn compact_history(&mut self) {
+    let bound = self.bound.as_mut().unwrap();
+    let mut history = std::mem::take(&mut bound.history);
+    history.retain(|value| !bound.drop_stale(value));
+    bound.history = history;
+  }
+
+  /// Adds a copy of the referenced value to the heap.
+  /// This is a convenience over push(value.clone()) for callers that only hold a reference.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, LeftHandedMedian};
+  ///
+  /// let values = vec![3, 1, 2];
+  /// let mut heap = MedianHeap::new(LeftHandedMedian);
+  /// for value in &values {
+  ///   heap.push_ref(value);
+  /// }
+  ///
+  /// assert_eq!(2, heap.get_median().unwrap());
+  /// ```
+  pub fn push_ref(&mut self, value: &T) {
+    self.push(*value);
+  }
+
+  /// Adds a value that's either owned or borrowed to the heap, copying it only if it's borrowed.
+  /// This accepts the output of generic code producing a Cow, which covers both push and push_ref.
+  ///
+  /// Example:
+  /// ```
+  /// use std::borrow::Cow;
+  /// use median_heap::{MedianHeap, LeftHandedMedian};
+  ///
+  /// let borrowed = 1;
+  /// let mut heap = MedianHeap::new(LeftHandedMedian);
+  /// heap.push_cow(Cow::Borrowed(&borrowed));
+  /// heap.push_cow(Cow::Owned(2));
+  ///
+  /// assert_eq!(vec![1, 2], heap.to_sorted_vec());
+  /// ```
+  pub fn push_cow(&mut self, value: Cow<'_, T>) {
+    self.push(value.into_owned());
+  }
+
+  /// Adds all values of an iterator to the heap.
+  /// The values are partitioned around the root of the max heap and the heaps are rebalanced once at the end, instead of after every value.
+  /// A heap created with with_policy pushes the values one by one instead, so its eviction policy is applied to each of them.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, LeftHandedMedian};
+  ///
+  /// let mut heap = MedianHeap::new(LeftHandedMedian);
+  /// heap.push(5);
+  /// heap.push_all(vec![1, 9, 3, 7]);
+  ///
+  /// assert_eq!(5, heap.len());
+  /// assert_eq!(5, heap.get_median().unwrap());
+  /// ```
+  ///
+  /// # Complexity
+  /// O(mlogn) for m values
+  pub fn push_all(&mut self, values: impl IntoIterator<Item = T>) {
+    // A bounded heap has to apply its eviction policy after every value, and a capped heap has to check every value against its cap.
+    if self.bound.is_some() || self.duplicates.is_some() {
+      self.batched(|heap| values.into_iter().for_each(|value| heap.push(value)));
+      return
+    }
+
+    let observed = self.observed_median();
+    let pivot = self.max_heap.peek().copied();
+    for value in values {
+      match pivot {
+        Some(pivot) if value > pivot => self.min_heap.push(Reverse(value)),
+        _ => self.max_heap.push(value),
+      }
+      self.track_added(&value);
+      self.cache_pushed(value);
+    }
+
+    self.rebalance();
+    self.finish_change(observed);
+  }
+
+  /// Adds all values of an iterator to the heap, the same as extend but callable without importing the Extend trait.
+  /// Both share push_all, so they always produce the same heap.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, IntegerMidpointMedian};
+  ///
+  /// let mut heap = MedianHeap::new(IntegerMidpointMedian);
+  /// heap.append_iter(1..=5);
+  ///
+  /// assert_eq!(3, heap.get_median().unwrap());
+  /// ```
+  ///
+  /// # Complexity
+  /// O(mlogn) for m values
+  pub fn append_iter(&mut self, iter: impl IntoIterator<Item = T>) {
+    self.push_all(iter);
+  }
+
+  /// Reads one value per line from the reader, pushes each of them and returns the number of values pushed.
+  /// Leading and trailing whitespace is trimmed and blank lines are skipped, so the input can be a column of numbers from a file or stdin.
+  ///
+  /// # Errors
+  /// Returns the error of the reader if reading fails.
+  /// If a line can't be parsed, an error of kind io::ErrorKind::InvalidData naming the line is returned.
+  /// The values read before the failing line stay in the heap.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, IntegerMidpointMedian};
+  ///
+  /// let mut heap: MedianHeap<i32, IntegerMidpointMedian> = MedianHeap::empty();
+  /// let input = "4\n 8 \n\n15\n";
+  ///
+  /// assert_eq!(3, heap.push_from_reader(input.as_bytes()).unwrap());
+  /// assert_eq!(8, heap.get_median().unwrap());
+  ///
+  /// let error = heap.push_from_reader("16\nx\n".as_bytes()).unwrap_err();
+  /// assert_eq!(std::io::ErrorKind::InvalidData, error.kind());
+  /// assert_eq!(4, heap.len());
+  /// ```
+  ///
+  /// # Complexity
+  /// O(mlogn) for m values
+  pub fn push_from_reader<R: BufRead>(&mut self, reader: R) -> io::Result<usize>
+  where
+    T: FromStr,
+    T::Err: Display,
+  {
+    self.batched(|heap| {
+      let mut count = 0;
+      for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() {
+          continue;
+        }
+
+        let value = line.parse().map_err(|error| {
+          io::Error::new(io::ErrorKind::InvalidData, format!("line {}: can't parse {:?}: {}", index + 1, line, error))
+        })?;
+        heap.push(value);
+        count += 1;
+      }
+      Ok(count)
+    })
+  }
+
+  /// Creates a new MedianHeap instance with the specified median kind from an iterator of results, like parsed values.
+  /// The iterator is consumed until the first error, without collecting the results first.
+  ///
+  /// # Errors
+  /// Returns the first error yielded by the iterator, the values before it are dropped along with the heap.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, IntegerMidpointMedian};
+  ///
+  /// let heap = MedianHeap::try_from_iter(IntegerMidpointMedian, "3 1 2".split(' ').map(str::parse::<i32>)).unwrap();
+  /// assert_eq!(2, heap.get_median().unwrap());
+  ///
+  /// let result = MedianHeap::try_from_iter(IntegerMidpointMedian, "3 x 2".split(' ').map(str::parse::<i32>));
+  /// assert!(result.is_err());
+  /// ```
+  ///
+  /// # Complexity
+  /// O(nlogn)
+  pub fn try_from_iter<E, I: IntoIterator<Item = Result<T, E>>>(median_kind: K, iter: I) -> Result<Self, E> {
+    let mut heap = MedianHeap::new(median_kind);
+    for value in iter {
+      heap.push(value?);
+    }
+    Ok(heap)
+  }
+
+  // Creates a heap with the specified median kind holding the values of the iterator, backing FromIterator and IteratorExt::collect_median.
+  pub(crate) fn from_iter_with<I: IntoIterator<Item = T>>(median_kind: K, iter: I) -> Self {
+    let iter = iter.into_iter();
+    let mut heap = MedianHeap::new(median_kind);
+    // Each half ends up with about half of the values, plus one for the push that precedes a rebalance.
+    let (lower, _) = iter.size_hint();
+    if lower > 0 {
+      heap.max_heap.reserve(lower.div_ceil(2) + 1);
+      heap.min_heap.reserve(lower / 2 + 1);
+    }
+    for value in iter {
+      heap.push(value);
+    }
+    heap
+  }
+
+  /// Adds all values of a slice that's sorted in ascending order to the heap.
+  /// The values are merged with the sorted contents of the heap and both heaps are rebuilt from the result, like from_sorted, which beats pushing a large batch value by value.
+  /// A heap created with with_policy or with_max_duplicates pushes the values one by one instead, like push_all.
+  ///
+  /// The values must be sorted, otherwise the heap gives incorrect results.
+  /// This is only checked by a debug assertion.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, LeftHandedMedian};
+  ///
+  /// let mut heap: MedianHeap<i32, LeftHandedMedian> = vec![5, 1, 9].into_iter().collect();
+  /// heap.extend_from_sorted(&[2, 3, 4, 10]);
+  ///
+  /// assert_eq!(vec![1, 2, 3, 4, 5, 9, 10], heap.to_sorted_vec());
+  /// assert_eq!(4, heap.get_median().unwrap());
+  /// ```
+  ///
+  /// # Complexity
+  /// O(nlogn + m) for m values
+  pub fn extend_from_sorted(&mut self, sorted: &[T]) {
+    debug_assert!(sorted.is_sorted(), "the values passed to extend_from_sorted must be sorted in ascending order");
+    if self.bound.is_some() || self.duplicates.is_some() {
+      self.batched(|heap| sorted.iter().for_each(|value| heap.push(*value)));
+      return
+    }
+
+    let observed = self.observed_median();
+    // The max heap sorts into the lower values in ascending order and the min heap into the upper values in descending order.
+    let mut merged = Vec::with_capacity(self.len() + sorted.len());
+    let lower = std::mem::take(&mut self.max_heap).into_sorted_vec();
+    let upper = std::mem::take(&mut self.min_heap).into_sorted_vec();
+    let mut current = lower.into_iter().chain(upper.into_iter().rev().map(|x| x.0)).peekable();
+    let mut batch = sorted.iter().copied().peekable();
+    while let (Some(a), Some(b)) = (current.peek(), batch.peek()) {
+      if a <= b {
+        merged.extend(current.next());
+      } else {
+        merged.extend(batch.next());
+      }
+    }
+    merged.extend(current);
+    merged.extend(batch);
+
+    self.cached_min = merged.first().copied();
+    self.cached_max = merged.last().copied();
+    let upper = merged.split_off(merged.len().div_ceil(2));
+    self.max_heap = LazyHeap::from(merged);
+    self.min_heap = upper.into_iter().map(Reverse).collect();
+    for value in sorted {
+      self.track_added(value);
+    }
+    self.finish_change(observed);
+  }
+
+  /// Removes and returns the median of the values in the heap.
+  /// If the heap is empty, the method returns None.
+  /// 
+  /// If two median candidates are found, the method pops both and merges them using the median kind to get the median.
+  /// 
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, LeftHandedMedian};
+  /// 
+  /// let mut heap = MedianHeap::new(LeftHandedMedian);
+  /// heap.push(1);
+  /// heap.push(2);
+  /// heap.push(3);
+  /// 
+  /// assert_eq!(2, heap.pop().unwrap());
+  /// assert_eq!(2, heap.len());
+  /// 
+  /// assert_eq!(1, heap.pop().unwrap());
+  /// assert_eq!(0, heap.len());
+  /// ```
+  /// 
+  /// # Complexity
+  /// O(logn) 
+  pub fn pop(&mut self) -> Option<T> {
+    if self.is_empty() {
+      return None
+    }
+    let observed = self.observed_median();
+    self.rebalance_within(1);
+
+    let median = if self.max_heap.len() == self.min_heap.len() {
+      let left = self.max_heap.pop().unwrap();
+      let right = self.min_heap.pop().unwrap().0;
+      self.track_removed(&left);
+      self.track_removed(&right);
+      self.median_kind.merge(&left, &right)
+    } else {
+      let median = if self.max_heap.len() > self.min_heap.len() {
+        self.max_heap.pop().unwrap()
+      } else {
+        self.min_heap.pop().unwrap().0
+      };
+      self.track_removed(&median);
+      median
+    };
+    self.cache_roots_removed();
+    self.finish_change(observed);
+    Some(median)
+  }
+
+  /// Removes the n most central values of the heap and returns them in the order they were removed.
+  /// If the heap holds fewer than n values, every value is removed.
+  ///
+  /// Unlike pop, every step removes a single stored value and never merges two candidates.
+  /// When the number of remaining values is even, the lower of the two central values is removed first, so the values alternate around the median.
+  /// Since every step takes the root of the larger half, the heaps stay balanced and only need a single rebalance at the end.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, IntegerMidpointMedian};
+  ///
+  /// let mut heap: MedianHeap<i32, IntegerMidpointMedian> = vec![1, 2, 3, 4, 5, 6, 7].into_iter().collect();
+  ///
+  /// assert_eq!(vec![4, 3, 5], heap.pop_median_n(3));
+  /// assert_eq!(vec![1, 2, 6, 7], heap.to_sorted_vec());
+  /// ```
+  ///
+  /// # Complexity
+  /// O(nlogm), where m is the number of values in the heap
+  pub fn pop_median_n(&mut self, n: usize) -> Vec<T> {
+    let observed = self.observed_median();
+    self.rebalance_within(1);
+    let mut popped = Vec::with_capacity(n.min(self.len()));
+    while popped.len() < n {
+      let value = if self.max_heap.len() >= self.min_heap.len() {
+        self.max_heap.pop()
+      } else {
+        self.min_heap.pop().map(|x| x.0)
+      };
+      match value {
+        Some(value) => {
+          self.track_removed(&value);
+          popped.push(value);
+        }
+        None => break,
+      }
+    }
+
+    self.rebalance();
+    self.cache_roots_removed();
+    self.finish_change(observed);
+    popped
+  }
+
+  /// Removes the median of the values in the heap, adds the specified value and returns the removed median.
+  /// The median is removed in the same way as pop does, so if two median candidates are found both are removed.
+  /// If the heap is empty, the value is added and the method returns None.
+  /// If the heap already holds as many copies of the value as with_max_duplicates allows, the heap is left unchanged and the method returns None, like push ignores the value.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, LeftHandedMedian};
+  ///
+  /// let mut heap = MedianHeap::new(LeftHandedMedian);
+  /// heap.push(1);
+  /// heap.push(2);
+  /// heap.push(3);
+  ///
+  /// assert_eq!(Some(2), heap.replace_median(10));
+  /// assert_eq!(3, heap.len());
+  /// assert_eq!(3, heap.get_median().unwrap());
+  /// ```
+  ///
+  /// # Complexity
+  /// O(logn)
+  pub fn replace_median(&mut self, value: T) -> Option<T> {
+    if self.duplicates.as_ref().is_some_and(|duplicates| duplicates.is_full(&value)) {
+      return None
+    }
+    let observed = self.observed_median();
+    self.rebalance_within(1);
+
+    // If the median is the root of the max heap and the value belongs in the max heap, the root can be replaced in place.
+    // A bounded heap goes through push instead, so the value is recorded for its eviction policy.
+    if self.bound.is_none() && self.max_heap.len() > self.min_heap.len() && self.min_heap.peek().is_none_or(|x| value <= x.0) {
+      let median = self.max_heap.replace_root(value);
+      self.track_removed(&median);
+      self.track_added(&value);
+      self.cache_roots_removed();
+      self.cache_pushed(value);
+      self.finish_change(observed);
+      return Some(median)
+    }
+
+    self.batched(|heap| {
+      let median = heap.pop();
+      heap.push(value);
+      median
+    })
+  }
+
+  /// Replaces a single instance of old with new, rebalancing the heap only once.
+  /// Returns true if old was found, otherwise new isn't added and the method returns false.
+  /// On a heap bounded with EvictionPolicy::EvictOldest, new counts as the most recently pushed value.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, LeftHandedMedian};
+  ///
+  /// let mut heap = MedianHeap::new(LeftHandedMedian);
+  /// heap.push(1);
+  /// heap.push(2);
+  /// heap.push(3);
+  ///
+  /// assert!(heap.update(&1, 10));
+  /// assert_eq!(3, heap.get_median().unwrap());
+  ///
+  /// assert!(!heap.update(&1, 0));
+  /// assert_eq!(3, heap.len());
+  /// ```
+  ///
+  /// # Complexity
+  /// O(n)
+  pub fn update(&mut self, old: &T, new: T) -> bool {
+    let observed = self.observed_median();
+    let found = if self.max_heap.peek().is_some_and(|root| old <= root) {
+      self.max_heap.remove_first(|x| x == old).is_some()
+    } else {
+      false
+    };
+    if !found && self.min_heap.remove_first(|x| x.0 == *old).is_none() {
+      return false
+    }
+
+    if self.max_heap.peek().is_none_or(|root| new <= *root) {
+      self.max_heap.push(new);
+    } else {
+      self.min_heap.push(Reverse(new));
+    }
+    self.rebalance();
+    self.track_removed(old);
+    self.track_added(&new);
+    if self.cached_min == Some(*old) || self.cached_max == Some(*old) {
+      self.refresh_extremes();
+    } else {
+      self.cache_pushed(new);
+    }
+
+    if let Some(bound) = self.bound.as_mut().filter(|bound| bound.policy == EvictionPolicy::EvictOldest) {
+      bound.history.push_back(new);
+      if bound.history.len() > 2 * bound.capacity {
+        self.compact_history();
+      }
+    }
+    self.finish_change(observed);
+    true
+  }
+
+  /// Removes every instance of the specified value from the heap.
+  /// 
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, LeftHandedMedian};
+  /// 
+  /// let mut heap = MedianHeap::new(LeftHandedMedian);
+  /// heap.push(1);
+  /// heap.push(2);
+  /// heap.push(3);
+  /// 
+  /// assert_eq!(3, heap.len());
+  /// assert_eq!(true, heap.contains(&2));
+  /// assert_eq!(2, heap.get_median().unwrap());
+  /// 
+  /// heap.delete(&2);
+  /// 
+  /// assert_eq!(2, heap.len());
+  /// assert_eq!(false, heap.contains(&2));
+  /// assert_eq!(1, heap.get_median().unwrap());
+  /// 
+  /// heap.delete(&3);
+  /// assert_eq!(1, heap.len());
+  /// assert_eq!(false, heap.contains(&3));
+  /// assert_eq!(1, heap.get_median().unwrap());
+  /// ```
+  /// 
+  /// # Complexity
+  /// O(n)
+  pub fn delete(&mut self, value: &T) {
+    if self.is_empty() {
+      return;
+    }
+
+    let observed = self.observed_median();
+
+    // Equal values can end up on both sides of the median, so both heaps have to be searched.
+    let len = self.len();
+    self.max_heap.retain(|x| x != value);
+    self.min_heap.retain(|x| x.0 != *value);
+    for _ in self.len()..len {
+      self.track_removed(value);
+    }
+
+    self.rebalance();
+    if self.cached_min == Some(*value) || self.cached_max == Some(*value) {
+      self.refresh_extremes();
+    }
+    self.finish_change(observed);
+  }
+
+  /// Returns true if the heap contains the specified value, false otherwise.
+  /// 
+  /// Example:
+  /// ```
+  /// # #![allow(deprecated)]
+  /// use median_heap::{MedianHeap, LeftHandedMedian};
+  /// 
+  /// let mut heap = MedianHeap::new(LeftHandedMedian);
+  /// heap.push(1);
+  /// heap.push(2);
+  /// 
+  /// assert_eq!(true, heap.has(&1));
+  /// assert_eq!(true, heap.has(&2));
+  /// assert_eq!(false, heap.has(&3));
+  /// 
+  /// heap.push(3);
+  /// 
+  /// assert_eq!(true, heap.has(&3));
+  /// ```
+  /// 
+  /// # Complexity
+  /// O(n)
+  #[deprecated(note = "use contains instead")]
+  pub fn has(&self, value: &T) -> bool {
+    self.contains(value)
+  }
+
+  pub fn peak_max(&self) -> Option<&T> {
+    self.max_heap.peek()
+  }
+
+  pub fn peak_min(&self) -> Option<&T> {
+    self.min_heap.peek().map(|x| &x.0)
+  }
+
+  /// Moves every value strictly greater than the current median into a new heap and returns it.
+  /// Values equal to the median, including the median itself when it's stored in the heap, stay in self, so self keeps the lower half plus the median.
+  /// Both heaps are rebalanced, the returned heap uses a clone of the median kind and is never bounded.
+  /// If the heap is empty, an empty heap is returned.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, LeftHandedMedian};
+  ///
+  /// let mut heap: MedianHeap<i32, LeftHandedMedian> = vec![1, 2, 3, 4, 5].into_iter().collect();
+  /// let upper = heap.split_off_above_median();
+  ///
+  /// assert_eq!(vec![&1, &2, &3], heap.sorted().collect::<Vec<_>>());
+  /// assert_eq!(vec![&4, &5], upper.sorted().collect::<Vec<_>>());
+  /// ```
+  ///
+  /// # Complexity
+  /// O(nlogn)
+  pub fn split_off_above_median(&mut self) -> MedianHeap<T, K>
+  where
+    K: Clone,
+  {
+    match self.get_median() {
+      Some(median) => self.split_at_value(&median),
+      None => MedianHeap::new(self.median_kind.clone()),
+    }
+  }
+
+  /// Moves every value strictly greater than pivot into a new heap and returns it, while the values less than or equal to pivot stay in self.
+  /// Both heaps are rebalanced, the returned heap uses a clone of the median kind and is never bounded.
+  /// This partitions the distribution into two ranges, for example to process them in parallel.
+  /// split_off_above_median is the same split with the median as the pivot.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, LeftHandedMedian};
+  ///
+  /// let mut heap: MedianHeap<i32, LeftHandedMedian> = vec![1, 2, 3, 4, 5, 6, 7].into_iter().collect();
+  /// let upper = heap.split_at_value(&2);
+  ///
+  /// assert_eq!(vec![1, 2], heap.to_sorted_vec());
+  /// assert_eq!(vec![3, 4, 5, 6, 7], upper.to_sorted_vec());
+  /// assert_eq!(Some(5), upper.get_median());
+  /// ```
+  ///
+  /// # Complexity
+  /// O(nlogn)
+  pub fn split_at_value(&mut self, pivot: &T) -> MedianHeap<T, K>
+  where
+    K: Clone,
+  {
+    let observed = self.observed_median();
+    let mut upper = MedianHeap::new(self.median_kind.clone());
+
+    // The values staying in each half are still ordered against the values staying in the other half, so both halves can be split in place.
+    let (lower_kept, lower_moved): (Vec<_>, Vec<_>) = std::mem::take(&mut self.max_heap)
+      .into_vec()
+      .into_iter()
+      .partition(|x| x <= pivot);
+    let (upper_kept, upper_moved): (Vec<_>, Vec<_>) = std::mem::take(&mut self.min_heap)
+      .into_vec()
+      .into_iter()
+      .partition(|x| x.0 <= *pivot);
+    self.max_heap = LazyHeap::from(lower_kept);
+    self.min_heap = LazyHeap::from(upper_kept);
+    upper.max_heap = lower_moved.into_iter().chain(upper_moved.into_iter().map(|x| x.0)).collect();
+
+    self.rebalance();
+    upper.rebalance();
+    self.recount_bound();
+    self.refresh_extremes();
+    upper.refresh_extremes();
+    self.recompute_tracked();
+    self.finish_change(observed);
+    upper
+  }
+
+  /// Returns the median absolute deviation (MAD), the median of the distances between every value and the median.
+  /// The median of the distances is computed in the same way as the median of the values, using the median kind of the heap.
+  /// If the heap is empty, or a distance doesn't fit in T, the method returns None.
+  /// A distance overflows a signed type when the values lie more than its maximum apart, e.g. i32::MIN and i32::MAX in the same heap.
+  ///
+  /// The distances are collected and sorted on every call, since they can't be maintained incrementally as the median moves.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, IntegerMidpointMedian};
+  ///
+  /// let heap: MedianHeap<i32, IntegerMidpointMedian> = vec![1, 1, 2, 2, 4, 6, 9].into_iter().collect();
+  ///
+  /// // The median is 2 and the distances are 1, 1, 0, 0, 2, 4, 7.
+  /// assert_eq!(Some(1), heap.mad());
+  /// ```
+  ///
+  /// # Complexity
+  /// O(nlogn)
+  pub fn mad(&self) -> Option<T>
+  where
+    T: CheckedSub,
+  {
+    let median = self.get_median()?;
+    let mut distances: Vec<T> = self.max_heap.iter()
+      .chain(self.min_heap.iter().map(|x| &x.0))
+      .map(|&x| if x < median { median.checked_sub(&x) } else { x.checked_sub(&median) })
+      .collect::<Option<_>>()?;
+    distances.sort();
+
+    let n = distances.len();
+    if n % 2 == 1 {
+      Some(distances[n / 2])
+    } else {
+      Some(self.median_kind.merge(&distances[n / 2 - 1], &distances[n / 2]))
+    }
+  }
+
+  /// Returns how much the median moved since the previous call, as the direction it moved in and the absolute difference between the current and the previously observed median.
+  /// The direction compares the current median with the previous one, so Ordering::Less means the median decreased.
+  /// The difference is never negative, so this works for unsigned types as well.
+  /// The current median is remembered for the next call, so calling this in a monitoring loop reports the drift between observations.
+  /// The first call after the heap is created, cleared or drained returns None, since there's no previous median to compare with.
+  /// If the heap is empty, the method returns None and the previously observed median is kept.
+  ///
+  /// Example:
+  /// ```
+  /// use std::cmp::Ordering;
+  /// use median_heap::{MedianHeap, LeftHandedMedian};
+  ///
+  /// let mut heap: MedianHeap<u32, LeftHandedMedian> = vec![1, 2, 3].into_iter().collect();
+  /// assert_eq!(None, heap.median_delta());
+  ///
+  /// heap.push(10);
+  /// heap.push(11);
+  /// assert_eq!(Some((Ordering::Greater, 1)), heap.median_delta());
+  ///
+  /// heap.pop_max();
+  /// heap.pop_max();
+  /// heap.pop_max();
+  /// assert_eq!(Some((Ordering::Less, 2)), heap.median_delta());
+  /// ```
+  ///
+  /// # Complexity
+  /// O(1)
+  pub fn median_delta(&mut self) -> Option<(Ordering, T)>
+  where
+    T: Sub<Output = T>,
+  {
+    let median = self.get_median()?;
+    let previous = self.last_median.replace(median)?;
+    let direction = median.cmp(&previous);
+    match direction {
+      Ordering::Less => Some((direction, previous - median)),
+      _ => Some((direction, median - previous)),
+    }
+  }
+
+  /// Swaps the values of two heaps, while each heap keeps its own median kind, capacity and eviction policy.
+  /// Swapping the whole heaps, median kinds included, is done with std::mem::swap.
+  ///
+  /// The chronological order of the values moves along with them, so EvictionPolicy::EvictOldest keeps evicting the oldest value.
+  /// If the values come from a heap that didn't track their order, they're treated as pushed in an arbitrary order.
+  /// A bounded heap that receives more values than its capacity keeps them, but every push evicts a value so it doesn't grow any further.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, LeftHandedMedian};
+  ///
+  /// let mut active: MedianHeap<i32, LeftHandedMedian> = vec![1, 2, 3, 4].into_iter().collect();
+  /// let mut standby = MedianHeap::new(LeftHandedMedian);
+  /// standby.push(10);
+  ///
+  /// active.swap_contents(&mut standby);
+  ///
+  /// assert_eq!(Some(10), active.get_median());
+  /// assert_eq!(Some(2), standby.get_median());
+  /// ```
+  ///
+  /// # Complexity
+  /// O(1), O(n) if the values move into a heap bounded with EvictionPolicy::EvictOldest from a heap that isn't
+  pub fn swap_contents(&mut self, other: &mut Self) {
+    let (observed, other_observed) = (self.observed_median(), other.observed_median());
+    std::mem::swap(&mut self.max_heap, &mut other.max_heap);
+    std::mem::swap(&mut self.min_heap, &mut other.min_heap);
+    std::mem::swap(&mut self.cached_min, &mut other.cached_min);
+    std::mem::swap(&mut self.cached_max, &mut other.cached_max);
+
+    let history = self.take_history();
+    let other_history = other.take_history();
+    self.adopt_history(other_history);
+    other.adopt_history(history);
+    // The heaps may have been created with a different slack.
+    self.rebalance();
+    other.rebalance();
+    self.recompute_tracked();
+    other.recompute_tracked();
+    self.recount_bound();
+    other.recount_bound();
+    self.finish_change(observed);
+    other.finish_change(other_observed);
+  }
+
+  // Takes the chronological order of the values out of a heap bounded with EvictOldest.
+  fn take_history(&mut self) -> Option<VecDeque<T>> {
+    self.bound.as_mut()
+      .filter(|bound| bound.policy == EvictionPolicy::EvictOldest)
+      .map(|bound| std::mem::take(&mut bound.history))
+  }
+
+  // Sets the chronological order of the values of a heap bounded with EvictOldest, in heap order if it isn't known.
+  fn adopt_history(&mut self, history: Option<VecDeque<T>>) {
+    let values = self.max_heap.iter().chain(self.min_heap.iter().map(|x| &x.0));
+    if let Some(bound) = self.bound.as_mut().filter(|bound| bound.policy == EvictionPolicy::EvictOldest) {
+      bound.history = history.unwrap_or_else(|| values.copied().collect());
+    }
+  }
+
+  /// Returns an iterator that pushes each value of `iter` into a new heap and yields the running median after each push.
+  /// The iterator is lazy, so it can be used on infinite iterators.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, LeftHandedMedian};
+  ///
+  /// let medians: Vec<i32> = MedianHeap::running_medians(LeftHandedMedian, vec![5, 1, 3, 2]).collect();
+  ///
+  /// assert_eq!(vec![5, 1, 3, 2], medians);
+  /// ```
+  ///
+  /// # Complexity
+  /// O(logn) per yielded median
+  pub fn running_medians<I: IntoIterator<Item = T>>(median_kind: K, iter: I) -> impl Iterator<Item = T> {
+    let mut heap = MedianHeap::new(median_kind);
+    iter.into_iter().map(move |value| {
+      heap.push(value);
+      heap.get_median().unwrap()
+    })
+  }
+}
+
+impl<T, K> MedianHeap<T, K> {
+  /// Returns the number of values in the heap.
+  /// 
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, LeftHandedMedian};
+  /// 
+  /// let mut heap = MedianHeap::new(LeftHandedMedian);
+  /// heap.push(1);
+  /// heap.push(2);
+  /// 
+  /// assert_eq!(2, heap.len());
+  /// ```
+  /// 
+  /// # Complexity
+  /// O(1)
+  pub fn len(&self) -> usize {
+    self.max_heap.len() + self.min_heap.len()
+  }
+
+  /// Returns true if the heap is empty, false otherwise.
+  /// 
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, LeftHandedMedian};
+  /// 
+  /// let mut heap = MedianHeap::new(LeftHandedMedian);
+  /// 
+  /// assert_eq!(true, heap.is_empty());
+  /// 
+  /// heap.push(1);
+  /// 
+  /// assert_eq!(false, heap.is_empty());
+  /// ```
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+
+  /// Removes all values from the heap.
+  /// 
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, LeftHandedMedian};
+  /// 
+  /// let mut heap = MedianHeap::new(LeftHandedMedian);
+  /// heap.push(1);
+  /// heap.push(2);
+  /// 
+  /// assert_eq!(2, heap.len());
+  /// 
+  /// heap.clear();
+  /// 
+  /// assert_eq!(0, heap.len());
+  /// ```
+  pub fn clear(&mut self) {
+    self.max_heap.clear();
+    self.min_heap.clear();
+    self.cached_min = None;
+    self.cached_max = None;
+    if let Some(moments) = self.moments.as_mut() {
+      moments.clear();
+    }
+    if let Some(duplicates) = self.duplicates.as_mut() {
+      duplicates.counts.clear();
+    }
+    if let Some(bound) = self.bound.as_mut() {
+      bound.clear();
+    }
+    self.last_median = None;
+  }
+
+  /// Returns the number of values the heap can hold without reallocating, which is the sum of the capacities of both internal heaps.
+  pub fn capacity(&self) -> usize {
+    self.max_heap.capacity() + self.min_heap.capacity()
+  }
+
+  /// Shrinks the capacity of both internal heaps as much as possible.
+  pub fn shrink_to_fit(&mut self) {
+    self.max_heap.shrink_to_fit();
+    self.min_heap.shrink_to_fit();
+  }
+
+  /// Removes all values from the heap and releases the memory held by its internal buffers.
+  /// This fully resets the heap, while keeping its configuration like the bound, the tracked moments and the duplicate limit.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, LeftHandedMedian};
+  ///
+  /// let mut heap: MedianHeap<i32, LeftHandedMedian> = (0..100).collect();
+  /// heap.clear_and_shrink();
+  ///
+  /// assert_eq!(0, heap.len());
+  /// assert_eq!(0, heap.capacity());
+  /// ```
+  pub fn clear_and_shrink(&mut self) {
+    self.clear();
+    self.shrink_to_fit();
+    if let Some(bound) = self.bound.as_mut() {
+      bound.history.shrink_to_fit();
+    }
+  }
+
+  /// Shrinks the capacity of the heap toward min_capacity, but never below the current length.
+  /// The requested capacity is split across both internal heaps, the max heap receiving the extra slot of an odd capacity, like it receives the extra value of an odd length.
+  /// If the current capacity is less than min_capacity, this does nothing.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, LeftHandedMedian};
+  ///
+  /// let mut heap: MedianHeap<i32, LeftHandedMedian> = (0..100).collect();
+  /// for value in 0..90 {
+  ///   heap.delete(&value);
+  /// }
+  ///
+  /// heap.shrink_to(20);
+  /// assert!(heap.capacity() >= 20);
+  /// assert!(heap.capacity() < 100);
+  /// ```
+  pub fn shrink_to(&mut self, min_capacity: usize) {
+    self.max_heap.shrink_to(min_capacity.div_ceil(2));
+    self.min_heap.shrink_to(min_capacity / 2);
+  }
+
+  // Shrinks a heap created with with_auto_capacity that has been mostly empty for long enough, or grows it back to its previous size if it's full.
+  fn adjust_capacity(&mut self) {
+    let (len, capacity) = (self.len(), self.capacity());
+    let full = self.max_heap.is_full() || self.min_heap.is_full();
+    let Some(tracker) = self.auto_capacity.as_mut() else {
+      return
+    };
+
+    tracker.high_water = tracker.high_water.max(len);
+    if (len as f64) < tracker.config.shrink_below * capacity as f64 {
+      tracker.low_operations += 1;
+    } else {
+      tracker.low_operations = 0;
+    }
+
+    if tracker.low_operations >= tracker.config.shrink_after {
+      let target = (2 * len).max(tracker.config.min_capacity);
+      // A heap draining in steps shrinks several times, so keep the largest length from before the first of them.
+      tracker.previous_high_water = tracker.previous_high_water.max(tracker.high_water);
+      tracker.high_water = len;
+      tracker.low_operations = 0;
+      self.shrink_to(target);
+    } else if full && tracker.previous_high_water > len {
+      // Reserve one extra slot per half, since a push goes to either half before the heaps are rebalanced.
+      let target = std::mem::take(&mut tracker.previous_high_water);
+      self.max_heap.reserve((target.div_ceil(2) + 1).saturating_sub(self.max_heap.len()));
+      self.min_heap.reserve((target / 2 + 1).saturating_sub(self.min_heap.len()));
+    }
+  }
+
+  /// Returns the number of values in the max heap minus the number of values in the min heap.
+  /// The heaps are kept balanced, so this is always 0 or 1. Any other value means the invariant was broken.
+  /// For a heap created with with_slack, it ranges from 1 - slack to slack instead.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, LeftHandedMedian};
+  ///
+  /// let mut heap = MedianHeap::new(LeftHandedMedian);
+  /// heap.push(1);
+  /// assert_eq!(1, heap.balance());
+  ///
+  /// heap.push(2);
+  /// assert_eq!(0, heap.balance());
+  /// ```
+  pub fn balance(&self) -> i64 {
+    self.max_heap.len() as i64 - self.min_heap.len() as i64
+  }
+
+  /// Returns true if the sizes of the heaps satisfy the invariant, which means the max heap holds as many values as the min heap or one more.
+  /// This is the same as checking that balance returns 0 or 1, or lies between 1 - slack and slack for a heap created with with_slack.
+  ///
+  /// # Complexity
+  /// O(1)
+  pub fn is_balanced(&self) -> bool {
+    let slack = self.slack as i64;
+    (1 - slack..=slack).contains(&self.balance())
+  }
+
+  /// Returns the number of values in the max heap (the lower half) and the min heap (the upper half).
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, LeftHandedMedian};
+  ///
+  /// let mut heap = MedianHeap::new(LeftHandedMedian);
+  /// heap.push(1);
+  /// heap.push(2);
+  /// heap.push(3);
+  ///
+  /// assert_eq!((2, 1), heap.halves_len());
+  /// ```
+  pub fn halves_len(&self) -> (usize, usize) {
+    (self.max_heap.len(), self.min_heap.len())
+  }
+
+  /// Returns the number of values in the max heap, the lower half of the values.
+  ///
+  /// In ascending order, the lower half holds the values at indices 0 to len_lower - 1 and the upper half the values at indices len_lower to len - 1.
+  /// The median candidates are the values at indices (len - 1) / 2 and len / 2, which are the same value when len is odd.
+  /// The heaps are kept balanced, so len_lower is len / 2 rounded up:
+  ///
+  /// - If len is odd, the median is the root of the max heap, at index len_lower - 1.
+  /// - If len is even, the median candidates are the root of the max heap, at index len_lower - 1, and the root of the min heap, at index len_lower.
+  ///
+  /// For a heap created with with_slack, the halves may differ by more, and the candidates can lie below the roots.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, LeftHandedMedian};
+  ///
+  /// let heap: MedianHeap<i32, LeftHandedMedian> = vec![10, 20, 30, 40, 50].into_iter().collect();
+  ///
+  /// assert_eq!(3, heap.len_lower());
+  /// assert_eq!(2, heap.len_upper());
+  /// assert_eq!(heap.get_sorted(heap.len_lower() - 1), heap.peak_max());
+  /// ```
+  ///
+  /// # Complexity
+  /// O(1)
+  pub fn len_lower(&self) -> usize {
+    self.max_heap.len()
+  }
+
+  /// Returns the number of values in the min heap, the upper half of the values.
+  /// The smallest value of the upper half is at index len_lower in ascending order, see len_lower.
+  ///
+  /// # Complexity
+  /// O(1)
+  pub fn len_upper(&self) -> usize {
+    self.min_heap.len()
+  }
+
+  /// Returns a reference to the median kind used to merge two median candidates.
+  pub fn median_kind(&self) -> &K {
+    &self.median_kind
+  }
+
+  /// Replaces the median kind used to merge two median candidates.
+  /// This only affects future merges in get_median and pop, the values in the heap aren't re-partitioned.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, MergeMedian};
+  ///
+  /// enum Candidate {
+  ///   Smaller,
+  ///   Larger,
+  /// }
+  ///
+  /// impl MergeMedian<i32> for Candidate {
+  ///   fn merge(&self, a: &i32, b: &i32) -> i32 {
+  ///     match self {
+  ///       Candidate::Smaller => *a.min(b),
+  ///       Candidate::Larger => *a.max(b),
+  ///     }
+  ///   }
+  /// }
+  ///
+  /// let mut heap = MedianHeap::new(Candidate::Smaller);
+  /// heap.push(1);
+  /// heap.push(2);
+  /// assert_eq!(1, heap.get_median().unwrap());
+  ///
+  /// heap.set_median_kind(Candidate::Larger);
+  /// assert_eq!(2, heap.get_median().unwrap());
+  /// ```
+  pub fn set_median_kind(&mut self, median_kind: K) {
+    self.median_kind = median_kind;
+  }
+}
+
+impl<T: Ord, K> MedianHeap<T, K> {
+  /// Removes all values from the heap and returns them as an iterator, keeping the allocated memory of the heap for reuse.
+  /// The values of the lower half are yielded first, followed by the values of the upper half, each in arbitrary order.
+  /// The heap is empty once the iterator is dropped, even if it isn't fully consumed.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, LeftHandedMedian};
+  ///
+  /// let mut heap = MedianHeap::new(LeftHandedMedian);
+  /// heap.push(1);
+  /// heap.push(2);
+  /// heap.push(3);
+  ///
+  /// let mut drained: Vec<i32> = heap.drain().collect();
+  /// drained.sort();
+  ///
+  /// assert_eq!(vec![1, 2, 3], drained);
+  /// assert!(heap.is_empty());
+  /// ```
+  pub fn drain(&mut self) -> impl Iterator<Item = T> + '_ {
+    self.cached_min = None;
+    self.cached_max = None;
+    if let Some(moments) = self.moments.as_mut() {
+      moments.clear();
+    }
+    if let Some(duplicates) = self.duplicates.as_mut() {
+      duplicates.counts.clear();
+    }
+    if let Some(bound) = self.bound.as_mut() {
+      bound.clear();
+    }
+    self.last_median = None;
+    self.max_heap.drain().chain(self.min_heap.drain().map(|x| x.0))
+  }
+
+  /// Returns an iterator over references to the values in the lower half of the heap (the max heap).
+  /// The values are yielded in heap order, not sorted.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, LeftHandedMedian};
+  ///
+  /// let mut heap = MedianHeap::new(LeftHandedMedian);
+  /// heap.push(1);
+  /// heap.push(2);
+  /// heap.push(3);
+  ///
+  /// let mut lower: Vec<&i32> = heap.iter_lower().collect();
+  /// lower.sort();
+  /// assert_eq!(vec![&1, &2], lower);
+  /// ```
+  pub fn iter_lower(&self) -> impl Iterator<Item = &T> {
+    self.max_heap.iter()
+  }
+
+  /// Returns an iterator over references to the values in the upper half of the heap (the min heap).
+  /// The values are yielded in heap order, not sorted.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, LeftHandedMedian};
+  ///
+  /// let mut heap = MedianHeap::new(LeftHandedMedian);
+  /// heap.push(1);
+  /// heap.push(2);
+  /// heap.push(3);
+  ///
+  /// assert_eq!(vec![&3], heap.iter_upper().collect::<Vec<_>>());
+  /// ```
+  pub fn iter_upper(&self) -> impl Iterator<Item = &T> {
+    self.min_heap.iter().map(|x| &x.0)
+  }
+
+  /// Returns an iterator over references to the values of both halves, each tagged with the half it's stored in.
+  /// This shows where the heap placed every value, which helps to understand and test how values are split.
+  /// The values of the lower half are yielded first, followed by the values of the upper half, each half in heap order, not sorted.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{Half, MedianHeap, LeftHandedMedian};
+  ///
+  /// let mut heap = MedianHeap::new(LeftHandedMedian);
+  /// heap.push(1);
+  /// heap.push(2);
+  ///
+  /// assert_eq!(vec![(&1, Half::Lower), (&2, Half::Upper)], heap.tagged_iter().collect::<Vec<_>>());
+  /// ```
+  pub fn tagged_iter(&self) -> impl Iterator<Item = (&T, Half)> {
+    self.iter_lower()
+      .map(|x| (x, Half::Lower))
+      .chain(self.iter_upper().map(|x| (x, Half::Upper)))
+  }
+
+  /// Returns a reference to the max heap holding the lower half of the values, to build queries the heap doesn't provide.
+  ///
+  /// This exposes the internal layout of the heap, which isn't part of its stable API.
+  /// Which half an equal value or the odd value ends up in may change between versions, so don't rely on it.
+  ///
+  /// A bounded heap drops evicted values from its halves lazily, see with_policy, so this first rebuilds the half without them, which is why it takes &mut self.
+  /// Use as_two_heaps to hold both halves at once.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, LeftHandedMedian};
+  ///
+  /// let mut heap: MedianHeap<i32, LeftHandedMedian> = vec![1, 2, 3].into_iter().collect();
+  ///
+  /// assert_eq!(Some(&2), heap.max_heap().peek());
+  /// ```
+  ///
+  /// # Complexity
+  /// O(1), O(n) if the half still holds evicted values
+  pub fn max_heap(&mut self) -> &BinaryHeap<T> {
+    self.max_heap.purged()
+  }
+
+  /// Returns a reference to the min heap holding the upper half of the values, to build queries the heap doesn't provide.
+  /// The values are wrapped in Reverse, use iter_upper to iterate over them unwrapped.
+  ///
+  /// Like max_heap, this exposes the internal layout of the heap, which isn't part of its stable API, and rebuilds the half without evicted values first.
+  ///
+  /// Example:
+  /// ```
+  /// use std::cmp::Reverse;
+  /// use median_heap::{MedianHeap, LeftHandedMedian};
+  ///
+  /// let mut heap: MedianHeap<i32, LeftHandedMedian> = vec![1, 2, 3].into_iter().collect();
+  ///
+  /// assert_eq!(Some(&Reverse(3)), heap.min_heap().peek());
+  /// ```
+  ///
+  /// # Complexity
+  /// O(1), O(n) if the half still holds evicted values
+  pub fn min_heap(&mut self) -> &BinaryHeap<Reverse<T>> {
+    self.min_heap.purged()
+  }
+
+  /// Returns references to both halves at once, the max heap holding the lower half of the values and the min heap holding the upper half.
+  /// See max_heap and min_heap.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, LeftHandedMedian};
+  ///
+  /// let mut heap: MedianHeap<i32, LeftHandedMedian> = vec![1, 2, 3, 4].into_iter().collect();
+  /// let (lower, upper) = heap.as_two_heaps();
+  ///
+  /// assert!(lower.iter().all(|x| upper.iter().all(|y| *x <= y.0)));
+  /// ```
+  ///
+  /// # Complexity
+  /// O(1), O(n) if the halves still hold evicted values
+  pub fn as_two_heaps(&mut self) -> (&BinaryHeap<T>, &BinaryHeap<Reverse<T>>) {
+    (self.max_heap.purged(), self.min_heap.purged())
+  }
+
+
+  /// Returns the fraction of values in the heap that are less than or equal to the specified value.
+  /// If the heap is empty, the method returns None.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, LeftHandedMedian};
+  ///
+  /// let mut heap = MedianHeap::new(LeftHandedMedian);
+  /// heap.push(1);
+  /// heap.push(2);
+  /// heap.push(3);
+  /// heap.push(4);
+  ///
+  /// assert_eq!(Some(0.5), heap.percentile_rank(&2));
+  /// assert_eq!(Some(0.0), heap.percentile_rank(&0));
+  /// assert_eq!(Some(1.0), heap.percentile_rank(&10));
+  /// ```
+  ///
+  /// # Complexity
+  /// O(n)
+  pub fn percentile_rank(&self, value: &T) -> Option<f64> {
+    if self.is_empty() {
+      return None
+    }
+
+    let count = self.max_heap.iter().filter(|x| *x <= value).count()
+      + self.min_heap.iter().filter(|x| x.0 <= *value).count();
+    Some(count as f64 / self.len() as f64)
+  }
+
+  /// Returns the number of values in the heap that are strictly less than the specified value.
+  /// Every value of the max heap is at most every value of the min heap, so only the half the value falls in has to be scanned, the other half is counted as a whole.
+  /// If the value lies between the two roots, no scan is needed at all.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, LeftHandedMedian};
+  ///
+  /// let heap: MedianHeap<i32, LeftHandedMedian> = vec![1, 2, 2, 3, 4].into_iter().collect();
+  ///
+  /// assert_eq!(1, heap.count_less(&2));
+  /// assert_eq!(4, heap.count_less(&4));
+  /// assert_eq!(0, heap.count_less(&0));
+  /// ```
+  ///
+  /// # Complexity
+  /// O(n) in the worst case, O(1) if the value lies between the roots of both heaps
+  pub fn count_less(&self, value: &T) -> usize {
+    match self.min_heap.peek() {
+      Some(root) if *value > root.0 => self.max_heap.len() + self.min_heap.iter().filter(|x| x.0 < *value).count(),
+      _ if self.max_heap.peek().is_some_and(|root| value > root) => self.max_heap.len(),
+      _ => self.max_heap.iter().filter(|x| *x < value).count(),
+    }
+  }
+
+  /// Returns the number of values in the heap that are strictly greater than the specified value.
+  /// Like count_less, only the half the value falls in has to be scanned.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, LeftHandedMedian};
+  ///
+  /// let heap: MedianHeap<i32, LeftHandedMedian> = vec![1, 2, 2, 3, 4].into_iter().collect();
+  ///
+  /// assert_eq!(2, heap.count_greater(&2));
+  /// assert_eq!(4, heap.count_greater(&1));
+  /// assert_eq!(0, heap.count_greater(&4));
+  /// ```
+  ///
+  /// # Complexity
+  /// O(n) in the worst case, O(1) if the value lies between the roots of both heaps
+  pub fn count_greater(&self, value: &T) -> usize {
+    match self.max_heap.peek() {
+      Some(root) if value < root => self.min_heap.len() + self.max_heap.iter().filter(|x| *x > value).count(),
+      _ if self.min_heap.peek().is_some_and(|root| *value < root.0) => self.min_heap.len(),
+      _ => self.min_heap.iter().filter(|x| x.0 > *value).count(),
+    }
+  }
+
+  /// Returns the smallest value in the heap.
+  /// If the heap is empty, the method returns None.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, LeftHandedMedian};
+  ///
+  /// let mut heap = MedianHeap::new(LeftHandedMedian);
+  /// heap.push(3);
+  /// heap.push(1);
+  /// heap.push(2);
+  ///
+  /// assert_eq!(Some(&1), heap.min());
+  /// ```
+  ///
+  /// # Complexity
+  /// O(1) while the smallest value is cached, O(n) after it was removed by pop_min or try_delete until the next push.
+  /// A heap bounded with EvictionPolicy::EvictExtreme or EvictFarthestFromMedian takes O(logn) instead, and one bounded with EvictOldest may take O(n) once it evicted its smallest value, since its pushes don't rescan the values
+  pub fn min(&self) -> Option<&T> {
+    self.cached_min.as_ref()
+      .or_else(|| self.bound.as_ref().and_then(|bound| bound.values.keys().next()))
+      .or_else(|| self.max_heap.iter().min())
+      .or_else(|| self.min_heap.peek().map(|x| &x.0))
+  }
+
+  /// Returns the largest value in the heap.
+  /// If the heap is empty, the method returns None.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, LeftHandedMedian};
+  ///
+  /// let mut heap = MedianHeap::new(LeftHandedMedian);
+  /// heap.push(3);
+  /// heap.push(1);
+  /// heap.push(2);
+  ///
+  /// assert_eq!(Some(&3), heap.max());
+  /// ```
+  ///
+  /// # Complexity
+  /// O(1) while the largest value is cached, O(n) after it was removed by pop_max or try_delete until the next push.
+  /// A heap bounded with EvictionPolicy::EvictExtreme or EvictFarthestFromMedian takes O(logn) instead, and one bounded with EvictOldest may take O(n) once it evicted its largest value, since its pushes don't rescan the values
+  pub fn max(&self) -> Option<&T> {
+    self.cached_max.as_ref()
+      .or_else(|| self.bound.as_ref().and_then(|bound| bound.values.keys().next_back()))
+      .or_else(|| self.min_heap.iter().min().map(|x| &x.0))
+      .or_else(|| self.max_heap.peek())
+  }
+
+  /// Removes and returns the smallest value in the heap.
+  /// If the heap is empty, the method returns None.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, LeftHandedMedian};
+  ///
+  /// let mut heap = MedianHeap::new(LeftHandedMedian);
+  /// heap.push(3);
+  /// heap.push(1);
+  /// heap.push(2);
+  ///
+  /// assert_eq!(Some(1), heap.pop_min());
+  /// assert_eq!(2, heap.len());
+  /// assert_eq!(2, heap.get_median().unwrap());
+  /// ```
+  ///
+  /// # Complexity
+  /// O(n), the max heap has to be rebuilt without its smallest value.
+  pub fn pop_min(&mut self) -> Option<T> {
+    let observed = self.observed_median();
+    let value = match self.max_heap.remove_least() {
+      Some(value) => value,
+      None => self.min_heap.pop()?.0,
+    };
+    self.rebalance();
+    self.forget_extreme(&value);
+    self.track_removed(&value);
+    self.finish_change(observed);
+    Some(value)
+  }
+
+  /// Removes and returns the largest value in the heap.
+  /// If the heap is empty, the method returns None.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, LeftHandedMedian};
+  ///
+  /// let mut heap = MedianHeap::new(LeftHandedMedian);
+  /// heap.push(3);
+  /// heap.push(1);
+  /// heap.push(2);
+  ///
+  /// assert_eq!(Some(3), heap.pop_max());
+  /// assert_eq!(2, heap.len());
+  /// assert_eq!(1, heap.get_median().unwrap());
+  /// ```
+  ///
+  /// # Complexity
+  /// O(n), the min heap has to be rebuilt without its largest value.
+  pub fn pop_max(&mut self) -> Option<T> {
+    let observed = self.observed_median();
+    let value = match self.min_heap.remove_least() {
+      Some(value) => value.0,
+      None => self.max_heap.pop()?,
+    };
+    self.rebalance();
+    self.forget_extreme(&value);
+    self.track_removed(&value);
+    self.finish_change(observed);
+    Some(value)
+  }
+
+  /// Returns true if the heap contains a value in the inclusive range from lo to hi, false otherwise.
+  ///
+  /// The roots of both heaps are checked first, which answers the query in constant time when the range contains a root or lies between them.
+  /// Otherwise only the half of the heap that the range falls into is scanned.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, LeftHandedMedian};
+  ///
+  /// let mut heap = MedianHeap::new(LeftHandedMedian);
+  /// heap.push(1);
+  /// heap.push(5);
+  /// heap.push(10);
+  ///
+  /// assert!(heap.may_contain(&4, &6));
+  /// assert!(heap.may_contain(&0, &1));
+  /// assert!(!heap.may_contain(&2, &4));
+  /// assert!(!heap.may_contain(&11, &20));
+  /// ```
+  ///
+  /// # Complexity
+  /// O(1) when the range contains a root or lies between the roots, O(n) otherwise
+  pub fn may_contain(&self, lo: &T, hi: &T) -> bool {
+    if self.is_empty() || lo > hi {
+      return false
+    }
+
+    let lower_root = self.max_heap.peek();
+    let upper_root = self.min_heap.peek().map(|x| &x.0);
+    let in_range = |x: &T| lo <= x && x <= hi;
+
+    if lower_root.is_some_and(in_range) || upper_root.is_some_and(in_range) {
+      return true
+    }
+
+    if lower_root.is_some_and(|root| hi < root) {
+      // Every value of the upper half is at least the lower root, so only the lower half can match.
+      self.max_heap.iter().any(in_range)
+    } else if upper_root.is_none_or(|root| lo > root) {
+      self.min_heap.iter().any(|x| in_range(&x.0))
+    } else {
+      // The range lies strictly between the two roots.
+      false
+    }
+  }
+
+  /// Returns true if the heap contains the specified value, false otherwise.
+  /// The roots of both heaps decide which halves are scanned, so a merged median that isn't stored in the heap is never reported as contained.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, IntegerMidpointMedian};
+  ///
+  /// let mut heap = MedianHeap::new(IntegerMidpointMedian);
+  /// heap.push(1);
+  /// heap.push(3);
+  ///
+  /// assert_eq!(2, heap.get_median().unwrap());
+  /// assert!(!heap.contains(&2));
+  /// assert!(heap.contains(&3));
+  /// ```
+  ///
+  /// # Complexity
+  /// O(n)
+  pub fn contains(&self, value: &T) -> bool {
+    // Every value of the max heap is at most its root and every value of the min heap is at least its root.
+    self.max_heap.peek().is_some_and(|root| value <= root) && self.max_heap.iter().any(|x| x == value)
+      || self.min_heap.peek().is_some_and(|root| *value >= root.0) && self.min_heap.iter().any(|x| x.0 == *value)
+  }
+
+  /// Removes a single instance of the specified value from the heap and returns it.
+  /// Unlike delete, this method reports why the removal failed.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, LeftHandedMedian, MedianHeapError};
+  ///
+  /// let mut heap = MedianHeap::new(LeftHandedMedian);
+  /// assert_eq!(Err(MedianHeapError::Empty), heap.try_delete(&1));
+  ///
+  /// heap.push(1);
+  /// heap.push(2);
+  /// heap.push(3);
+  ///
+  /// assert_eq!(Ok(3), heap.try_delete(&3));
+  /// assert_eq!(Err(MedianHeapError::NotFound), heap.try_delete(&3));
+  /// assert_eq!(2, heap.len());
+  /// ```
+  ///
+  /// # Complexity
+  /// O(n)
+  pub fn try_delete(&mut self, value: &T) -> Result<T, MedianHeapError> {
+    if self.is_empty() {
+      return Err(MedianHeapError::Empty)
+    }
+
+    let observed = self.observed_median();
+    let removed = if self.max_heap.peek().is_some_and(|root| value <= root) {
+      self.max_heap.remove_first(|x| x == value)
+    } else {
+      None
+    };
+    let removed = match removed {
+      Some(removed) => removed,
+      None => self.min_heap.remove_first(|x| x.0 == *value).ok_or(MedianHeapError::NotFound)?.0,
+    };
+
+    self.rebalance();
+    self.forget_extreme(&removed);
+    self.track_removed(&removed);
+    self.finish_change(observed);
+    Ok(removed)
+  }
+
+  /// Returns an iterator over references to the values in the heap in ascending order, without modifying the heap.
+  /// The references are collected and sorted when this method is called.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, LeftHandedMedian};
+  ///
+  /// let mut heap = MedianHeap::new(LeftHandedMedian);
+  /// heap.push(3);
+  /// heap.push(1);
+  /// heap.push(2);
+  ///
+  /// assert_eq!(vec![&1, &2, &3], heap.sorted().collect::<Vec<_>>());
+  /// assert_eq!(3, heap.len());
+  /// ```
+  ///
+  /// # Complexity
+  /// O(nlogn) to create the iterator
+  pub fn sorted(&self) -> impl Iterator<Item = &T> {
+    let mut values: Vec<&T> = self.max_heap.iter().chain(self.min_heap.iter().map(|x| &x.0)).collect();
+    values.sort();
+    values.into_iter()
+  }
+
+  /// Consumes the heap and returns its values in ascending order.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, LeftHandedMedian};
+  ///
+  /// let heap: MedianHeap<i32, LeftHandedMedian> = vec![3, 1, 2].into_iter().collect();
+  ///
+  /// assert_eq!(vec![1, 2, 3], heap.into_sorted_vec());
+  /// ```
+  ///
+  /// # Complexity
+  /// O(nlogn)
+  pub fn into_sorted_vec(self) -> Vec<T> {
+    // Every value of the max heap is at most every value of the min heap, so the sorted halves can simply be concatenated.
+    let mut values = self.max_heap.into_sorted_vec();
+    values.extend(self.min_heap.into_sorted_vec().into_iter().rev().map(|x| x.0));
+    values
+  }
+
+  /// Returns the n-th smallest value in the heap, counting from 0, so nth_smallest(0) is the minimum.
+  /// If the heap holds n values or fewer, the method returns None.
+  ///
+  /// The lower half holds the smallest values, so only the half containing the n-th value is searched, with a quickselect over references to its values.
+  /// The median of an odd number of values is nth_smallest(len / 2).
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, LeftHandedMedian};
+  ///
+  /// let heap: MedianHeap<i32, LeftHandedMedian> = vec![50, 10, 40, 20, 30].into_iter().collect();
+  ///
+  /// assert_eq!(Some(&10), heap.nth_smallest(0));
+  /// assert_eq!(Some(&40), heap.nth_smallest(3));
+  /// assert_eq!(None, heap.nth_smallest(5));
+  /// ```
+  ///
+  /// # Complexity
+  /// O(n)
+  pub fn nth_smallest(&self, n: usize) -> Option<&T> {
+    if n < self.max_heap.len() {
+      let mut values: Vec<&T> = self.max_heap.iter().collect();
+      let (_, value, _) = values.select_nth_unstable(n);
+      Some(*value)
+    } else if n < self.len() {
+      let mut values: Vec<&T> = self.min_heap.iter().map(|x| &x.0).collect();
+      let (_, value, _) = values.select_nth_unstable(n - self.max_heap.len());
+      Some(*value)
+    } else {
+      None
+    }
+  }
+
+  /// Returns the value at the specified index of the values in ascending order, as if the heap were a sorted vector.
+  /// If the index is out of bounds, the method returns None.
+  /// This is the same as nth_smallest, named after slice::get for positional access.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, LeftHandedMedian};
+  ///
+  /// let heap: MedianHeap<i32, LeftHandedMedian> = vec![30, 10, 20].into_iter().collect();
+  ///
+  /// assert_eq!(Some(&20), heap.get_sorted(1));
+  /// assert_eq!(None, heap.get_sorted(3));
+  /// ```
+  ///
+  /// # Complexity
+  /// O(n)
+  pub fn get_sorted(&self, index: usize) -> Option<&T> {
+    self.nth_smallest(index)
+  }
+
+  /// Returns the n-th largest value in the heap, counting from 0, so nth_largest(0) is the maximum.
+  /// If the heap holds n values or fewer, the method returns None.
+  /// See nth_smallest.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, LeftHandedMedian};
+  ///
+  /// let heap: MedianHeap<i32, LeftHandedMedian> = vec![50, 10, 40, 20, 30].into_iter().collect();
+  ///
+  /// assert_eq!(Some(&50), heap.nth_largest(0));
+  /// assert_eq!(Some(&20), heap.nth_largest(3));
+  /// assert_eq!(None, heap.nth_largest(5));
+  /// ```
+  ///
+  /// # Complexity
+  /// O(n)
+  pub fn nth_largest(&self, n: usize) -> Option<&T> {
+    let index = self.len().checked_sub(n + 1)?;
+    self.nth_smallest(index)
+  }
+
+  // Records a removed value in the tracked variance, the duplicate counts and the stored values of a bounded heap.
+  fn track_removed(&mut self, value: &T) {
+    if let Some(moments) = self.moments.as_mut() {
+      moments.remove(value);
+    }
+    if let Some(duplicates) = self.duplicates.as_mut() {
+      duplicates.remove(value);
+    }
+    if let Some(bound) = self.bound.as_mut() {
+      bound.remove(value);
+    }
+  }
+
+  // Recomputes the tracked variance and duplicate counts from scratch, after many values were moved or removed at once.
+  fn recompute_tracked(&mut self) {
+    if self.moments.is_none() && self.duplicates.is_none() {
+      return
+    }
+
+    if let Some(moments) = self.moments.as_mut() {
+      moments.clear();
+    }
+    if let Some(duplicates) = self.duplicates.as_mut() {
+      duplicates.counts.clear();
+    }
+    for value in self.max_heap.iter().chain(self.min_heap.iter().map(|x| &x.0)) {
+      if let Some(moments) = self.moments.as_mut() {
+        moments.add(value);
+      }
+      if let Some(duplicates) = self.duplicates.as_mut() {
+        duplicates.add(value);
+      }
+    }
+  }
+
+  // Forgets a cached extreme equal to a removed value, since another copy of it may or may not be left.
+  fn forget_extreme(&mut self, removed: &T) {
+    if self.cached_min.as_ref() == Some(removed) {
+      self.cached_min = None;
+    }
+    if self.cached_max.as_ref() == Some(removed) {
+      self.cached_max = None;
+    }
+  }
+
+  // Moves roots between the heaps until the max heap holds at most one value more than the min heap, and never fewer.
+  fn rebalance(&mut self) {
+    self.rebalance_within(self.slack);
+  }
+
+  // Moves roots between the heaps until the max heap holds at most slack values more than the min heap and the min heap at most slack - 1 values more than the max heap.
+  // A slack of 1 restores the strict invariant, which methods removing the roots as the median rely on.
+  fn rebalance_within(&mut self, slack: usize) {
+    while self.max_heap.len() > self.min_heap.len() + slack {
+      let value = self.max_heap.pop().unwrap();
+      self.min_heap.push(Reverse(value));
+    }
+    while self.min_heap.len() + 1 > self.max_heap.len() + slack {
+      let value = self.min_heap.pop().unwrap().0;
+      self.max_heap.push(value);
+    }
+  }
+
+  /// Returns references to the two median candidates, the root of the max heap and the root of the min heap, without cloning them or merging them.
+  /// For a heap created with with_slack, the candidates are the two middle values, which may lie below the root of the larger heap.
+  /// If the number of values is odd, there's a single middle value and both references point to it.
+  /// If the heap is empty, the method returns None.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, IntegerMidpointMedian};
+  ///
+  /// let mut heap: MedianHeap<i32, IntegerMidpointMedian> = vec![1, 2, 4, 5].into_iter().collect();
+  /// assert_eq!(Some((&2, &4)), heap.median_candidate_refs());
+  ///
+  /// heap.push(3);
+  /// assert_eq!(Some((&3, &3)), heap.median_candidate_refs());
+  /// ```
+  ///
+  /// # Complexity
+  /// O(1), O(klogk) for a heap created with a slack of k
+  pub fn median_candidate_refs(&self) -> Option<(&T, &T)> {
+    let n = self.len();
+    if n == 0 {
+      return None
+    }
+
+    let left = self.middle_value((n - 1) / 2)?;
+    let right = if n.is_multiple_of(2) { self.middle_value(n / 2)? } else { left };
+    Some((left, right))
+  }
+
+  /// Returns the median of the values in the heap, handling the two cases a median kind doesn't tell apart with separate closures.
+  /// If the number of values is odd, odd is called with the middle value.
+  /// If it's even, even is called with the two middle values, the smaller one first.
+  /// The closures are only used for this call, nothing is stored or modified.
+  /// If the heap is empty, the method returns None and neither closure is called.
+  ///
+  /// See median_with to only replace the merging of two candidates.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, IntegerMidpointMedian};
+  ///
+  /// let mut heap = MedianHeap::new(IntegerMidpointMedian);
+  /// heap.push(10);
+  /// heap.push(20);
+  ///
+  /// // Takes the lower candidate on even counts and flags the exact middle value on odd counts.
+  /// let strategy = |heap: &MedianHeap<i32, IntegerMidpointMedian>| heap.median_with_strategy(|x| -x, |a, _| *a);
+  /// assert_eq!(Some(10), strategy(&heap));
+  ///
+  /// heap.push(30);
+  /// assert_eq!(Some(-20), strategy(&heap));
+  /// ```
+  ///
+  /// # Complexity
+  /// O(1), O(klogk) for a heap created with a slack of k
+  pub fn median_with_strategy(&self, odd: impl Fn(&T) -> T, even: impl Fn(&T, &T) -> T) -> Option<T> {
+    let (left, right) = self.median_candidate_refs()?;
+    if self.len().is_multiple_of(2) {
+      Some(even(left, right))
+    } else {
+      Some(odd(left))
+    }
+  }
+
+  // Returns the value at the specified index of the values in ascending order, for an index close to the middle.
+  // The value is found by counting from the root of the heap holding it, so this is fast when the heaps are nearly balanced.
+  fn middle_value(&self, index: usize) -> Option<&T> {
+    let lower = self.max_heap.len();
+    if index < lower {
+      self.max_heap.nth_from_root(lower - 1 - index)
+    } else {
+      self.min_heap.nth_from_root(index - lower).map(|x| &x.0)
+    }
+  }
+
+  /// Checks the invariants of the heap and describes the first one that's broken.
+  /// The heap is valid if it's balanced, see is_balanced, and the root of the max heap is at most the root of the min heap.
+  ///
+  /// The public methods keep the heap valid, so this is meant for tests and for checking a heap built with from_parts.
+  ///
+  /// Example:
+  /// ```
+  /// use std::cmp::Reverse;
+  /// use std::collections::BinaryHeap;
+  /// use median_heap::{MedianHeap, IntegerMidpointMedian};
+  ///
+  /// let heap: MedianHeap<i32, IntegerMidpointMedian> = vec![1, 2, 3].into_iter().collect();
+  /// assert_eq!(Ok(()), heap.validate());
+  ///
+  /// let heap = MedianHeap::from_parts(IntegerMidpointMedian, BinaryHeap::from(vec![5]), BinaryHeap::from(vec![Reverse(2)]));
+  /// assert!(heap.validate().is_err());
+  /// ```
+  ///
+  /// # Complexity
+  /// O(1)
+  pub fn validate(&self) -> Result<(), String> {
+    if !self.is_balanced() {
+      return Err(format!(
+        "the heap is unbalanced: the max heap holds {} values and the min heap {}",
+        self.max_heap.len(),
+        self.min_heap.len(),
+      ))
+    }
+    if let (Some(left), Some(Reverse(right))) = (self.max_heap.peek(), self.min_heap.peek()) {
+      if left > right {
+        return Err("the heap is out of order: the root of the max heap is greater than the root of the min heap".to_string())
+      }
+    }
+    Ok(())
+  }
+
+  // Panics if the heap is invalid, see validate.
+  // The check only runs with the debug-checks feature, otherwise the method compiles to nothing.
+  #[inline(always)]
+  fn check_invariants(&self) {
+    #[cfg(feature = "debug-checks")]
+    if let Err(message) = self.validate() {
+      panic!("MedianHeap invariant violated: {}", message);
+    }
+  }
+
+  // Returns the median before an operation that may notify the median change callback, or None if there's no callback to notify.
+  fn observed_median(&self) -> Option<T> {
+    self.on_median_change.as_ref().and_then(|observer| (observer.median)(self))
+  }
+
+  // Calls the median change callback if the median differs from the one observed before the operation.
+  fn notify_median_change(&mut self, observed: Option<T>) {
+    let Some(median) = self.observed_median() else {
+      return
+    };
+    if Some(&median) != observed.as_ref() {
+      if let Some(observer) = self.on_median_change.as_mut() {
+        let callback = observer.callback.get_mut().unwrap_or_else(PoisonError::into_inner);
+        callback(&median);
+      }
+    }
+  }
+
+  // Finishes an operation that added or removed values: adjusts an automatically managed capacity, notifies the median change callback and checks the invariants.
+  fn finish_change(&mut self, observed: Option<T>) {
+    self.adjust_capacity();
+    self.notify_median_change(observed);
+    self.check_invariants();
+  }
+
+  // Runs an operation built on several pushes or pops with the median change callback detached, so it's notified once for the whole operation.
+  fn batched<R>(&mut self, operation: impl FnOnce(&mut Self) -> R) -> R {
+    let observed = self.observed_median();
+    let observer = self.on_median_change.take();
+    let result = operation(self);
+    self.on_median_change = observer;
+    self.finish_change(observed);
+    result
+  }
+}
+
+impl<T: Ord + ToPrimitive, K> MedianHeap<T, K> {
+  /// Returns the median of the values in the heap as a f64.
+  /// If two median candidates are found, they're averaged in floating point, so the median of integer values isn't truncated.
+  /// If the heap is empty or a candidate can't be represented as a f64, the method returns None.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, IntegerMidpointMedian};
+  ///
+  /// let mut heap = MedianHeap::new(IntegerMidpointMedian);
+  /// heap.push(2);
+  /// heap.push(3);
+  ///
+  /// assert_eq!(2, heap.get_median().unwrap());
+  /// assert_eq!(Some(2.5), heap.median_f64());
+  /// ```
+  ///
+  /// # Complexity
+  /// O(1)
+  pub fn median_f64(&self) -> Option<f64> {
+    let (left, right) = self.median_candidate_refs()?;
+    if self.len().is_multiple_of(2) {
+      Some((left.to_f64()? + right.to_f64()?) / 2.0)
+    } else {
+      left.to_f64()
+    }
+  }
+
+  /// Returns the median of the values in the heap as a f64, linearly interpolated halfway between the two middle values when the number of values is even.
+  /// This is the median numpy.median and most statistics packages report, regardless of the median kind of the heap.
+  ///
+  /// - If the heap is empty, the method returns None.
+  /// - If the heap holds a single value, or any odd number of values, the middle value is returned as is.
+  /// - If the heap holds an even number of values, the two middle values are halved before they're added, so values near the edges of the f64 range can't overflow to infinity.
+  ///
+  /// It differs from median_f64 only in that last step, and from IntegerMidpointMedian in never truncating the result.
+  /// If a middle value can't be represented as a f64, the method returns None.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, IntegerMidpointMedian};
+  ///
+  /// let mut heap = MedianHeap::new(IntegerMidpointMedian);
+  /// assert_eq!(None, heap.median_interpolated());
+  ///
+  /// heap.push(1);
+  /// assert_eq!(Some(1.0), heap.median_interpolated());
+  ///
+  /// heap.push(4);
+  /// assert_eq!(2, heap.get_median().unwrap());
+  /// assert_eq!(Some(2.5), heap.median_interpolated());
+  /// ```
+  ///
+  /// # Complexity
+  /// O(1)
+  pub fn median_interpolated(&self) -> Option<f64> {
+    let (left, right) = self.median_candidate_refs()?;
+    if self.len().is_multiple_of(2) {
+      Some(left.to_f64()? / 2.0 + right.to_f64()? / 2.0)
+    } else {
+      left.to_f64()
+    }
+  }
+
+  /// Returns the arithmetic mean of the values in the heap.
+  /// The values are summed in a f64 accumulator, so integer sums are exact up to 2^53 and larger sums are rounded.
+  /// If the heap is empty, a value can't be represented as a f64 or the sum overflows the f64 range, the method returns None.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, LeftHandedMedian};
+  ///
+  /// let mut heap = MedianHeap::new(LeftHandedMedian);
+  /// heap.push(i64::MAX);
+  /// heap.push(i64::MAX);
+  ///
+  /// assert_eq!(Some(i64::MAX as f64), heap.checked_mean());
+  /// ```
+  ///
+  /// # Complexity
+  /// O(n)
+  pub fn checked_mean(&self) -> Option<f64> {
+    if self.is_empty() {
+      return None
+    }
+
+    let mut sum = 0.0;
+    for value in self.max_heap.iter().chain(self.min_heap.iter().map(|x| &x.0)) {
+      sum += value.to_f64()?;
+    }
+    let mean = sum / self.len() as f64;
+    mean.is_finite().then_some(mean)
+  }
+
+  /// Returns the count, median, extremes, mean and 90th and 99th percentiles of the values in the heap, computed from a single sorted copy of the values.
+  /// This is cheaper than calling the separate query methods, which scan the heap once each. See MedianSummary.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, LeftHandedMedian};
+  ///
+  /// let heap: MedianHeap<i32, LeftHandedMedian> = vec![4, 1, 3, 2].into_iter().collect();
+  /// let summary = heap.summary();
+  ///
+  /// assert_eq!(Some(2), summary.median);
+  /// assert_eq!(Some(1), summary.min);
+  /// assert_eq!(Some(4), summary.max);
+  /// assert_eq!(Some(2.5), summary.mean);
+  /// ```
+  ///
+  /// # Complexity
+  /// O(nlogn)
+  pub fn summary(&self) -> MedianSummary<T>
+  where
+    T: Copy,
+    K: MergeMedian<T>,
+  {
+    let sorted = self.to_sorted_vec();
+    let percentile = |p: usize| sorted.len().checked_sub(1).map(|last| sorted[last * p / 100]);
+    let mean = if sorted.is_empty() {
+      None
+    } else {
+      sorted.iter().map(|x| x.to_f64()).sum::<Option<f64>>()
+        .map(|sum| sum / sorted.len() as f64)
+        .filter(|mean| mean.is_finite())
+    };
+
+    MedianSummary {
+      count: sorted.len(),
+      median: self.get_median(),
+      min: sorted.first().copied(),
+      max: sorted.last().copied(),
+      mean,
+      p90: percentile(90),
+      p99: percentile(99),
+    }
+  }
+
+  /// Starts tracking the variance of the values with Welford's algorithm, so variance and std_dev take O(1) instead of O(n).
+  /// The values already in the heap are accounted for once, after which every push and removal updates the variance in O(1).
+  ///
+  /// Removing a value reverses its update, which loses some precision every time.
+  /// The error stays small while the values are of a similar magnitude, but a delete-heavy workload over values that vary by many orders of magnitude can drift.
+  /// Methods that remove many values at once, like remove_outliers, recompute the variance from scratch.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, IntegerMidpointMedian};
+  ///
+  /// let mut heap: MedianHeap<i32, IntegerMidpointMedian> = vec![2, 4, 4, 4].into_iter().collect();
+  /// heap.track_variance();
+  /// heap.push(5);
+  /// heap.push(5);
+  /// heap.push(7);
+  /// heap.push(9);
+  ///
+  /// assert_eq!(Some(4.0), heap.variance());
+  /// assert_eq!(Some(2.0), heap.std_dev());
+  /// ```
+  ///
+  /// # Complexity
+  /// O(n)
+  pub fn track_variance(&mut self) {
+    self.moments = Some(Moments {
+      count: 0,
+      mean: 0.0,
+      m2: 0.0,
+      to_f64: |x| x.to_f64().unwrap_or(f64::NAN),
+    });
+    self.recompute_tracked();
+  }
+
+  /// Returns the population variance of the values in the heap, the mean of the squared distances from the mean.
+  /// If the heap is empty, a value can't be represented as a f64 or the result overflows the f64 range, the method returns None.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, IntegerMidpointMedian};
+  ///
+  /// let heap: MedianHeap<i32, IntegerMidpointMedian> = vec![1, 3].into_iter().collect();
+  ///
+  /// assert_eq!(Some(1.0), heap.variance());
+  /// ```
+  ///
+  /// # Complexity
+  /// O(1) if the variance is tracked, see track_variance, O(n) otherwise
+  pub fn variance(&self) -> Option<f64> {
+    let variance = match &self.moments {
+      Some(moments) if moments.count > 0 => moments.m2 / moments.count as f64,
+      Some(_) => return None,
+      None => {
+        let mean = self.checked_mean()?;
+        let values = self.max_heap.iter().chain(self.min_heap.iter().map(|x| &x.0));
+        values.map(|x| (x.to_f64().unwrap_or(f64::NAN) - mean).powi(2)).sum::<f64>() / self.len() as f64
+      }
+    };
+    variance.is_finite().then_some(variance)
+  }
+
+  /// Returns the population standard deviation of the values in the heap, the square root of the variance.
+  /// If the heap is empty, a value can't be represented as a f64 or the result overflows the f64 range, the method returns None.
+  ///
+  /// # Complexity
+  /// O(1) if the variance is tracked, see track_variance, O(n) otherwise
+  pub fn std_dev(&self) -> Option<f64> {
+    self.variance().map(f64::sqrt)
+  }
+
+  /// Divides the range from the smallest to the largest value into the specified number of equally wide bins and counts the values in each of them.
+  /// Every bin is returned as a (lo, hi, count) tuple, where hi is the lo of the next bin and the last bin ends at the largest value.
+  /// A bin holds the values from its lo up to but excluding its hi, except for the last bin, which also holds the largest value.
+  ///
+  /// The bin edges are computed as f64 and converted back to T, so for integers they're rounded toward zero.
+  /// Bins whose lo edges round to the same value are merged, so fewer bins are returned when the range holds fewer representable values than bins, for example for integers.
+  /// If the heap is empty or bins is 0, the method returns an empty vector.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, LeftHandedMedian};
+  ///
+  /// let heap: MedianHeap<i32, LeftHandedMedian> = vec![0, 1, 2, 5, 6, 9, 10].into_iter().collect();
+  ///
+  /// assert_eq!(vec![(0, 5, 3), (5, 10, 4)], heap.histogram(2));
+  /// ```
+  ///
+  /// # Complexity
+  /// O(nlog(bins))
+  pub fn histogram(&self, bins: usize) -> Vec<(T, T, usize)>
+  where
+    T: Copy + NumCast,
+  {
+    let (Some(&min), Some(&max)) = (self.min(), self.max()) else {
+      return Vec::new()
+    };
+    if bins == 0 {
+      return Vec::new()
+    }
+
+    // The range is measured in f64, since subtracting the extremes overflows T when they lie far apart.
+    let (start, range) = min.to_f64().zip(max.to_f64()).map_or((0.0, 0.0), |(min, max)| (min, max - min));
+    // Rounding to f64 can move an edge past the extremes for wide integer types, so the edges are clamped to them.
+    let mut edges: Vec<T> = (0..bins)
+      .map(|i| T::from(start + range * i as f64 / bins as f64).map_or(max, |edge| edge.clamp(min, max)))
+      .collect();
+    // Edges that round to the same value would leave empty bins of zero width behind.
+    edges.dedup();
+    let mut counts = vec![0; edges.len()];
+    for value in self.max_heap.iter().chain(self.min_heap.iter().map(|x| &x.0)) {
+      counts[edges.partition_point(|edge| edge <= value) - 1] += 1;
     }
+
+    edges.push(max);
+    edges.windows(2).zip(counts).map(|(edge, count)| (edge[0], edge[1], count)).collect()
   }
 }
 
-impl<T: Ord + Add + Copy, K: MergeMedian<T>> MedianHeap<T, K> {
-  /// Returns the median of the values in the heap.
-  /// If the heap is empty, the method returns None.
-  /// 
+impl<T: Ord + Clone, K> MedianHeap<T, K> {
+  /// Returns a snapshot of the two halves of the heap as owned vectors.
+  /// The first vector holds the lower half (the max heap) and the second vector holds the upper half (the min heap).
+  /// Both vectors are sorted in ascending order.
+  ///
   /// Example:
   /// ```
   /// use median_heap::{MedianHeap, LeftHandedMedian};
-  /// 
+  ///
   /// let mut heap = MedianHeap::new(LeftHandedMedian);
-  /// heap.push(1);
-  /// heap.push(2);
   /// heap.push(3);
+  /// heap.push(1);
   /// heap.push(4);
-  /// 
-  /// assert_eq!(2, heap.get_median().unwrap());
+  /// heap.push(2);
+  ///
+  /// assert_eq!((vec![1, 2], vec![3, 4]), heap.halves());
   /// ```
-  /// 
-  /// In this example, the median of the values 1, 2, 3, 4 is 2.
-  /// 
+  ///
   /// # Complexity
-  /// O(1)
-  pub fn get_median(&self) -> Option<T> {
-    if self.max_heap.len() == 0 && self.min_heap.len() == 0 {
-      return None
-    }
+  /// O(nlogn)
+  pub fn halves(&self) -> (Vec<T>, Vec<T>) {
+    let mut lower: Vec<T> = self.max_heap.iter().cloned().collect();
+    let mut upper: Vec<T> = self.min_heap.iter().map(|x| x.0.clone()).collect();
+    lower.sort();
+    upper.sort();
+    (lower, upper)
+  }
 
-    // If the number of values in the max heap and min heap are equal, two candidates are found.
-    // If not then the median is the root of the larger heap.
-    if self.max_heap.len() == self.min_heap.len() {
-      // Merge the two candidates to get the median.
-      let median = self.median_kind.merge(self.max_heap.peek().unwrap(), &self.min_heap.peek().unwrap().0);
-      return Some(median)
-    } else if self.max_heap.len() > self.min_heap.len() {
-      return Some(*self.max_heap.peek().unwrap())
-    } else {
-      return Some(self.min_heap.peek().unwrap().0)
-    }
+  /// Returns a vector holding a copy of every value in the heap in ascending order, without modifying the heap.
+  /// Unlike into_sorted_vec, this method borrows the heap, which makes it suitable for exporting a snapshot of the values.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, LeftHandedMedian};
+  ///
+  /// let heap: MedianHeap<i32, LeftHandedMedian> = vec![3, 1, 2].into_iter().collect();
+  ///
+  /// assert_eq!(vec![1, 2, 3], heap.to_sorted_vec());
+  /// assert_eq!(3, heap.len());
+  /// ```
+  ///
+  /// # Complexity
+  /// O(nlogn)
+  pub fn to_sorted_vec(&self) -> Vec<T> {
+    let (mut lower, upper) = self.halves();
+    lower.extend(upper);
+    lower
   }
-}
 
-impl<T: Ord + Add + Copy, K: MergeMedian<T>> MedianHeap<T, K> {
-  /// Adds a value to the heap.
-  /// 
+  /// Returns every distinct value in the heap once, in ascending order.
+  ///
   /// Example:
   /// ```
   /// use median_heap::{MedianHeap, LeftHandedMedian};
-  /// 
-  /// let mut heap = MedianHeap::new(LeftHandedMedian);
-  /// heap.push(2);
-  /// 
-  /// assert_eq!(2, heap.get_median().unwrap());
-  /// 
-  /// heap.push(1);
-  /// 
-  /// assert_eq!(1, heap.get_median().unwrap());
+  ///
+  /// let heap: MedianHeap<i32, LeftHandedMedian> = vec![3, 1, 3, 2, 1].into_iter().collect();
+  ///
+  /// assert_eq!(vec![1, 2, 3], heap.distinct());
   /// ```
-  pub fn push(&mut self, value: T) {
-    // If the heap is empty, push the value to the max heap.
-    if self.is_empty() {
-      self.max_heap.push(value);
-      return
-    }
+  ///
+  /// # Complexity
+  /// O(nlogn)
+  pub fn distinct(&self) -> Vec<T> {
+    let mut values = self.to_sorted_vec();
+    values.dedup();
+    values
+  }
 
-    // Get the median of the values in the heap.
-    let median = self.get_median().unwrap();
-    // If the value is less than the median, push it to the max heap.
-    // If the value is greater than the median, push it to the min heap.
-    if value < median {
-      self.max_heap.push(value);
-    } else {
-      self.min_heap.push(Reverse(value));
-    }
+  /// Returns the number of distinct values in the heap, which shows how much the values repeat when compared to len.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, LeftHandedMedian};
+  ///
+  /// let heap: MedianHeap<i32, LeftHandedMedian> = vec![3, 1, 3, 2, 1].into_iter().collect();
+  ///
+  /// assert_eq!(3, heap.distinct_count());
+  /// assert_eq!(5, heap.len());
+  /// ```
+  ///
+  /// # Complexity
+  /// O(nlogn)
+  pub fn distinct_count(&self) -> usize {
+    self.distinct().len()
+  }
 
-    // Balance the heaps.
-    // If the difference between the number of values in the max heap and min heap is greater than 1, pop the root of the larger heap and push it to the smaller heap.
-    // This ensures that the difference between the number of values in the max heap and min heap is at most 1.
-    if self.max_heap.len() > self.min_heap.len() + 1 {
-      let value = self.max_heap.pop().unwrap();
-      self.min_heap.push(Reverse(value));
-    } else if self.min_heap.len() > self.max_heap.len() {
-      let value = self.min_heap.pop().unwrap().0;
-      self.max_heap.push(value);
-    }
+  /// Returns an iterator over copies of every value in the heap in ascending order, without modifying the heap.
+  /// Unlike to_sorted_vec, the values are produced one at a time, so the sorted result is never materialized.
+  ///
+  /// Each half is walked through a heap of references to its values, which is built when the iteration reaches that half.
+  /// This takes one pointer per value of a single half instead of a copy of every value, but the values are still cloned as they're yielded, since the heap is only borrowed.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, LeftHandedMedian};
+  ///
+  /// let heap: MedianHeap<i32, LeftHandedMedian> = vec![4, 1, 3, 2].into_iter().collect();
+  ///
+  /// assert_eq!(vec![1, 2, 3, 4], heap.sorted_iter().collect::<Vec<_>>());
+  /// assert_eq!(Some(1), heap.sorted_iter().next());
+  /// ```
+  ///
+  /// # Complexity
+  /// O(n) to start each half, O(logn) per value
+  pub fn sorted_iter(&self) -> impl Iterator<Item = T> + '_ {
+    // The max heap pops its largest value first, so its values are wrapped in Reverse to pop the smallest first.
+    let mut lower: Option<BinaryHeap<Reverse<&T>>> = None;
+    let lower = std::iter::from_fn(move || {
+      let lower = lower.get_or_insert_with(|| self.max_heap.iter().map(Reverse).collect());
+      lower.pop().map(|x| x.0.clone())
+    });
+    let mut upper: Option<BinaryHeap<&Reverse<T>>> = None;
+    let upper = std::iter::from_fn(move || {
+      let upper = upper.get_or_insert_with(|| self.min_heap.iter().collect());
+      upper.pop().map(|x| x.0.clone())
+    });
+    lower.chain(upper)
   }
 
-  /// Removes and returns the median of the values in the heap.
+  /// Returns the first quartile (the 25th percentile) of the values in the heap.
+  /// The quartile is the value at index floor((n - 1) / 4) in ascending order, so it's always a value stored in the heap.
   /// If the heap is empty, the method returns None.
-  /// 
-  /// If two median candidates are found, the method pops both and merges them using the median kind to get the median.
-  /// 
+  ///
   /// Example:
   /// ```
   /// use median_heap::{MedianHeap, LeftHandedMedian};
-  /// 
+  ///
   /// let mut heap = MedianHeap::new(LeftHandedMedian);
-  /// heap.push(1);
-  /// heap.push(2);
-  /// heap.push(3);
-  /// 
-  /// assert_eq!(2, heap.pop().unwrap());
-  /// assert_eq!(2, heap.len());
-  /// 
-  /// assert_eq!(1, heap.pop().unwrap());
-  /// assert_eq!(0, heap.len());
+  /// for value in 1..=9 {
+  ///   heap.push(value);
+  /// }
+  ///
+  /// assert_eq!(Some(3), heap.q1());
   /// ```
-  /// 
+  ///
   /// # Complexity
-  /// O(logn) 
-  pub fn pop(&mut self) -> Option<T> {
-    if self.is_empty() {
-      return None
-    }
-
-    if self.max_heap.len() == self.min_heap.len() {
-      let left = self.max_heap.pop().unwrap();
-      let right = self.min_heap.pop().unwrap().0;
-      let median = self.median_kind.merge(&left, &right);
-      return Some(median)
-    } else if self.max_heap.len() > self.min_heap.len() {
-      return Some(self.max_heap.pop().unwrap())
-    } else {
-      return Some(self.min_heap.pop().unwrap().0)
-    }
+  /// O(n)
+  pub fn q1(&self) -> Option<T> {
+    let index = self.len().checked_sub(1)? / 4;
+    self.nth_smallest(index).cloned()
   }
 
-  /// Removes the specified value from the heap.
-  /// 
+  /// Returns the third quartile (the 75th percentile) of the values in the heap.
+  /// The quartile is the value at index floor(3 * (n - 1) / 4) in ascending order, so it's always a value stored in the heap.
+  /// If the heap is empty, the method returns None.
+  ///
   /// Example:
   /// ```
   /// use median_heap::{MedianHeap, LeftHandedMedian};
-  /// 
+  ///
   /// let mut heap = MedianHeap::new(LeftHandedMedian);
-  /// heap.push(1);
-  /// heap.push(2);
-  /// heap.push(3);
-  /// 
-  /// assert_eq!(3, heap.len());
-  /// assert_eq!(true, heap.has(&2));
-  /// assert_eq!(2, heap.get_median().unwrap());
-  /// 
-  /// heap.delete(&2);
-  /// 
-  /// assert_eq!(2, heap.len());
-  /// assert_eq!(false, heap.has(&2));
-  /// assert_eq!(1, heap.get_median().unwrap());
-  /// 
-  /// heap.delete(&3);
-  /// assert_eq!(1, heap.len());
-  /// assert_eq!(false, heap.has(&3));
-  /// assert_eq!(1, heap.get_median().unwrap());
+  /// for value in 1..=9 {
+  ///   heap.push(value);
+  /// }
+  ///
+  /// assert_eq!(Some(7), heap.q3());
   /// ```
-  /// 
+  ///
   /// # Complexity
   /// O(n)
-  pub fn delete(&mut self, value: &T) {
-    if self.is_empty() {
-      return;
-    }
-
-    let median = self.get_median().unwrap();
-
-    if value == &median {
-      self.pop();
-      return;
-    }
-
-    if *value < median {
-      self.max_heap.retain(|x| x != value);
-    } else {
-      self.min_heap.retain(|x| x.0 != *value);
-    }
+  pub fn q3(&self) -> Option<T> {
+    let index = self.len().checked_sub(1)? * 3 / 4;
+    self.nth_smallest(index).cloned()
   }
 
-  /// Returns true if the heap contains the specified value, false otherwise.
-  /// 
+  /// Returns the interquartile range of the values in the heap, which is q3 - q1.
+  /// If the heap is empty, the method returns None.
+  ///
   /// Example:
   /// ```
   /// use median_heap::{MedianHeap, LeftHandedMedian};
-  /// 
+  ///
   /// let mut heap = MedianHeap::new(LeftHandedMedian);
-  /// heap.push(1);
-  /// heap.push(2);
-  /// 
-  /// assert_eq!(true, heap.has(&1));
-  /// assert_eq!(true, heap.has(&2));
-  /// assert_eq!(false, heap.has(&3));
-  /// 
-  /// heap.push(3);
-  /// 
-  /// assert_eq!(true, heap.has(&3));
+  /// for value in 1..=9 {
+  ///   heap.push(value);
+  /// }
+  ///
+  /// assert_eq!(Some(4), heap.iqr());
   /// ```
-  /// 
+  ///
   /// # Complexity
   /// O(n)
-  pub fn has(&self, value: &T) -> bool {
-    if self.is_empty() {
-      return false
-    }
-
-    let median = self.get_median().unwrap();
-
-    if *value == median {
-      return true
-    }
-    else if *value < median {
-      // Search in the max heap.
-      self.max_heap.iter().any(|x| *x == *value)
-    } else {
-      // Search in the min heap.
-      self.min_heap.iter().any(|x| x.0 == *value)
-    }
-  }
-
-  pub fn peak_max(&self) -> Option<&T> {
-    self.max_heap.peek()
-  }
-
-  pub fn peak_min(&self) -> Option<&T> {
-    self.min_heap.peek().map(|x| &x.0)
+  pub fn iqr(&self) -> Option<T>
+  where
+    T: Sub<Output = T>,
+  {
+    Some(self.q3()? - self.q1()?)
   }
-}
 
-impl<T, K> MedianHeap<T, K> {
-  /// Returns the number of values in the heap.
-  /// 
+  /// Removes all values outside of the range [q1 - k * iqr, q3 + k * iqr] from the heap and rebalances once.
+  /// With k = 1.5 these are Tukey's fences, the common rule for detecting outliers.
+  /// Values that can't be represented as a f64 are kept.
+  ///
   /// Example:
   /// ```
   /// use median_heap::{MedianHeap, LeftHandedMedian};
-  /// 
+  ///
   /// let mut heap = MedianHeap::new(LeftHandedMedian);
-  /// heap.push(1);
-  /// heap.push(2);
-  /// 
-  /// assert_eq!(2, heap.len());
+  /// for value in [-100, 1, 2, 3, 4, 5, 6, 7, 8, 100] {
+  ///   heap.push(value);
+  /// }
+  ///
+  /// heap.remove_outliers(1.5);
+  ///
+  /// assert_eq!(8, heap.len());
+  /// assert_eq!(Some(&1), heap.min());
+  /// assert_eq!(Some(&8), heap.max());
   /// ```
-  /// 
+  ///
   /// # Complexity
-  /// O(1)
-  pub fn len(&self) -> usize {
-    self.max_heap.len() + self.min_heap.len()
+  /// O(n)
+  pub fn remove_outliers(&mut self, k: f64)
+  where
+    T: ToPrimitive,
+  {
+    let (Some(q1), Some(q3)) = (self.q1().and_then(|x| x.to_f64()), self.q3().and_then(|x| x.to_f64())) else {
+      return
+    };
+    let iqr = q3 - q1;
+    let (lower_fence, upper_fence) = (q1 - k * iqr, q3 + k * iqr);
+    let keep = |x: &T| x.to_f64().is_none_or(|x| lower_fence <= x && x <= upper_fence);
+
+    let observed = self.observed_median();
+    self.max_heap.retain(keep);
+    self.min_heap.retain(|x| keep(&x.0));
+    self.rebalance();
+    self.recount_bound();
+    self.refresh_extremes();
+    self.recompute_tracked();
+    self.finish_change(observed);
   }
 
-  /// Returns true if the heap is empty, false otherwise.
-  /// 
+  /// Removes every value within the specified range from the heap and returns the number of removed values.
+  /// Both halves are filtered in a single pass and the heap is rebalanced once, so this is cheaper than deleting the values one by one.
+  ///
   /// Example:
   /// ```
   /// use median_heap::{MedianHeap, LeftHandedMedian};
-  /// 
-  /// let mut heap = MedianHeap::new(LeftHandedMedian);
-  /// 
-  /// assert_eq!(true, heap.is_empty());
-  /// 
-  /// heap.push(1);
-  /// 
-  /// assert_eq!(false, heap.is_empty());
+  ///
+  /// let mut heap: MedianHeap<i32, LeftHandedMedian> = (1..=9).collect();
+  ///
+  /// assert_eq!(2, heap.delete_range(..=2));
+  /// assert_eq!(3, heap.delete_range(7..));
+  /// assert_eq!(vec![3, 4, 5, 6], heap.to_sorted_vec());
+  /// assert_eq!(4, heap.get_median().unwrap());
   /// ```
-  pub fn is_empty(&self) -> bool {
-    self.len() == 0
+  ///
+  /// # Complexity
+  /// O(n)
+  pub fn delete_range<R: RangeBounds<T>>(&mut self, range: R) -> usize {
+    let observed = self.observed_median();
+    let len = self.len();
+    self.max_heap.retain(|x| !range.contains(x));
+    self.min_heap.retain(|x| !range.contains(&x.0));
+    let removed = len - self.len();
+    if removed > 0 {
+      self.rebalance();
+      self.recount_bound();
+      self.refresh_extremes();
+      self.recompute_tracked();
+      self.finish_change(observed);
+    }
+    removed
   }
 
-  /// Removes all values from the heap.
-  /// 
+  /// Rebuilds both heaps from the sorted values, so every invariant holds afterwards whatever state the heap was in.
+  /// The values are split at the midpoint like from_sorted, and the cached extremes, tracked variance and duplicate counts are recomputed.
+  /// This recovers a heap built from halves that weren't ordered, for example with from_parts.
+  ///
   /// Example:
   /// ```
+  /// use std::cmp::Reverse;
+  /// use std::collections::BinaryHeap;
   /// use median_heap::{MedianHeap, LeftHandedMedian};
-  /// 
-  /// let mut heap = MedianHeap::new(LeftHandedMedian);
-  /// heap.push(1);
-  /// heap.push(2);
-  /// 
-  /// assert_eq!(2, heap.len());
-  /// 
-  /// heap.clear();
-  /// 
-  /// assert_eq!(0, heap.len());
+  ///
+  /// let mut heap = MedianHeap::from_parts(LeftHandedMedian, BinaryHeap::from(vec![5, 9]), BinaryHeap::from(vec![Reverse(1)]));
+  /// assert!(heap.validate().is_err());
+  ///
+  /// heap.rebuild();
+  /// assert_eq!(Ok(()), heap.validate());
+  /// assert_eq!(5, heap.get_median().unwrap());
   /// ```
-  pub fn clear(&mut self) {
-    self.max_heap.clear();
-    self.min_heap.clear();
+  ///
+  /// # Complexity
+  /// O(nlogn)
+  pub fn rebuild(&mut self) {
+    let mut values = std::mem::take(&mut self.max_heap).into_vec();
+    values.extend(std::mem::take(&mut self.min_heap).into_vec().into_iter().map(|x| x.0));
+    values.sort();
+
+    let upper = values.split_off(values.len().div_ceil(2));
+    self.max_heap = LazyHeap::from(values);
+    self.min_heap = upper.into_iter().map(Reverse).collect();
+    self.recount_bound();
+    self.refresh_extremes();
+    self.recompute_tracked();
+    self.check_invariants();
+  }
+
+  // Recomputes both cached extremes, by scanning the heap unless it's bounded.
+  fn refresh_extremes(&mut self) {
+    self.cached_min = None;
+    self.cached_max = None;
+    self.cached_min = self.min().cloned();
+    self.cached_max = self.max().cloned();
+  }
+
+  // Recounts the stored values of a bounded heap, after many values were moved or removed at once.
+  // A heap bounded with EvictOldest keeps the newest history entries of every stored value instead, so none of them is stale.
+  fn recount_bound(&mut self) {
+    let Some(bound) = self.bound.as_mut() else {
+      return
+    };
+    bound.copy = Some(|x| x.clone());
+    let mut values = BTreeMap::new();
+    for value in self.max_heap.iter().chain(self.min_heap.iter().map(|x| &x.0)) {
+      *values.entry(value.clone()).or_insert(0) += 1;
+    }
+
+    if bound.counts_values() {
+      bound.values = values;
+      return
+    }
+    let mut history = VecDeque::with_capacity(bound.history.len());
+    for value in bound.history.iter().rev() {
+      if let Some(count) = values.get_mut(value).filter(|count| **count > 0) {
+        *count -= 1;
+        history.push_front(value.clone());
+      }
+    }
+    bound.history = history;
+    bound.stale.clear();
   }
 }
 
-impl<T: Debug + Copy, K> Debug for MedianHeap<T, K> {
+impl<T: Ord + Debug + Copy, K> Debug for MedianHeap<T, K> {
   /// Formats the heap for debugging purposes.
   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
     write!(f, "max_heap: {:?}, min_heap: {:?}", self.max_heap, self.min_heap.iter().map(|x| x.0).collect::<Vec<_>>())
@@ -371,13 +3654,40 @@ impl<T: Debug + Copy, K> Debug for MedianHeap<T, K> {
 //   }
 // }
 
-impl<T: Ord + Add<Output = T> + Copy, K: MergeMedian<T> + Default> FromIterator<T> for MedianHeap<T, K> {
+impl<T: Ord + Copy, K: MergeMedian<T> + Default> FromIterator<T> for MedianHeap<T, K> {
   fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
-    let mut heap = MedianHeap::new(K::default());
-    for value in iter {
-      heap.push(value);
-    }
-    heap
+    MedianHeap::from_iter_with(K::default(), iter)
+  }
+}
+
+impl<T: Ord, K: MergeMedian<T> + Default> From<Vec<T>> for MedianHeap<T, K> {
+  /// Creates a heap from the values of the vector by sorting them and splitting them at the midpoint, see from_sorted.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{MedianHeap, IntegerMidpointMedian};
+  ///
+  /// let heap: MedianHeap<_, IntegerMidpointMedian> = vec![3, 1, 2].into();
+  ///
+  /// assert_eq!(2, heap.get_median().unwrap());
+  /// ```
+  fn from(mut values: Vec<T>) -> Self {
+    values.sort();
+    MedianHeap::from_sorted(K::default(), values)
+  }
+}
+
+impl<T: Ord + Clone, K: MergeMedian<T> + Default> From<&[T]> for MedianHeap<T, K> {
+  /// Creates a heap from a copy of the values of the slice, see From<Vec<T>>.
+  fn from(values: &[T]) -> Self {
+    MedianHeap::from(values.to_vec())
+  }
+}
+
+impl<T: Ord + Copy, K: MergeMedian<T>> Extend<T> for MedianHeap<T, K> {
+  /// Adds every value of the iterator to the heap in the same way as push_all.
+  fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+    self.push_all(iter);
   }
 }
 
@@ -387,6 +3697,98 @@ impl<T: Ord + Clone, K: Clone> Clone for MedianHeap<T, K> {
       median_kind: self.median_kind.clone(),
       max_heap: self.max_heap.clone(),
       min_heap: self.min_heap.clone(),
+      bound: self.bound.clone(),
+      cached_min: self.cached_min.clone(),
+      cached_max: self.cached_max.clone(),
+      moments: self.moments.clone(),
+      duplicates: self.duplicates.clone(),
+      last_median: self.last_median.clone(),
+      slack: self.slack,
+      auto_capacity: self.auto_capacity.clone(),
+      // A boxed closure can't be cloned, so the clone doesn't notify anyone.
+      on_median_change: None,
     }
   }
-}
\ No newline at end of file
+
+  /// Copies the contents and the median kind of source into self, reusing the existing allocations of self's heaps.
+  fn clone_from(&mut self, source: &Self) {
+    self.median_kind.clone_from(&source.median_kind);
+    self.max_heap.clone_from(&source.max_heap);
+    self.min_heap.clone_from(&source.min_heap);
+    self.bound.clone_from(&source.bound);
+    self.cached_min.clone_from(&source.cached_min);
+    self.cached_max.clone_from(&source.cached_max);
+    self.moments.clone_from(&source.moments);
+    self.duplicates.clone_from(&source.duplicates);
+    self.last_median.clone_from(&source.last_median);
+    self.slack = source.slack;
+    self.auto_capacity.clone_from(&source.auto_capacity);
+    self.on_median_change = None;
+  }
+}
+
+/// Returns the median of the values in the slice, merging two median candidates with the specified median kind.
+/// It's a shortcut for a single median, without creating a MedianHeap.
+/// The slice itself isn't modified, the median is taken from a sorted copy of it.
+/// If the slice is empty, the function returns None.
+///
+/// Example:
+/// ```
+/// use median_heap::{median_of, LeftHandedMedian, IntegerMidpointMedian};
+///
+/// assert_eq!(Some(3), median_of(IntegerMidpointMedian, &[4, 1, 2, 9]));
+/// assert_eq!(Some(2), median_of(LeftHandedMedian, &[4, 1, 2, 9]));
+/// assert_eq!(None, median_of(IntegerMidpointMedian, &[] as &[i32]));
+/// ```
+///
+/// # Complexity
+/// O(nlogn)
+pub fn median_of<T: Ord + Copy, K: MergeMedian<T>>(median_kind: K, data: &[T]) -> Option<T> {
+  if data.is_empty() {
+    return None
+  }
+
+  let mut sorted = data.to_vec();
+  sorted.sort_unstable();
+  let middle = (sorted.len() - 1) / 2;
+  if sorted.len().is_multiple_of(2) {
+    Some(median_kind.merge(&sorted[middle], &sorted[middle + 1]))
+  } else {
+    Some(sorted[middle])
+  }
+}
+
+// Compares the distance from the minimum to the median with the distance from the median to the maximum, see EvictFarthestFromMedian.
+pub(crate) fn compare_distances<T: Ord + Copy + Sub<Output = T>>(min: &T, median: &T, max: &T) -> Ordering {
+  (*median - *min).cmp(&(*max - *median))
+}
+
+/// Returns an iterator over the running median of the values, taken over a window that slides one value at a time.
+/// The first median is yielded once window values have been read, after which every value yields the median of itself and the window - 1 values before it.
+/// If the iterator holds fewer than window values, nothing is yielded.
+///
+/// The window is a MedianHeap bounded by EvictOldest, so every step takes O(log(window)) amortized instead of sorting the window again.
+///
+/// Example:
+/// ```
+/// use median_heap::{rolling_median, IntegerMidpointMedian};
+///
+/// let medians: Vec<i32> = rolling_median(IntegerMidpointMedian, vec![1, 5, 2, 8, 3, 3], 3).collect();
+///
+/// assert_eq!(vec![2, 5, 3, 3], medians);
+/// ```
+///
+/// # Panics
+/// Panics if window is 0.
+pub fn rolling_median<T, K, I>(median_kind: K, iter: I, window: usize) -> impl Iterator<Item = T>
+where
+  T: Ord + Copy,
+  K: MergeMedian<T>,
+  I: IntoIterator<Item = T>,
+{
+  let mut heap = MedianHeap::with_policy(median_kind, window, EvictionPolicy::EvictOldest);
+  iter.into_iter().filter_map(move |value| {
+    heap.push(value);
+    (heap.len() == window).then(|| heap.get_median().unwrap())
+  })
+}
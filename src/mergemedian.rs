@@ -1,4 +1,7 @@
-use std::ops::{Add, Div};
+use core::ops::{Add, Div};
+use num::integer::Average;
+#[cfg(feature = "std")]
+use num::traits::Float;
 use num::traits::One;
 
 /// MergeMedian is a trait that defines a method to merge two values of the same type into a single value.
@@ -28,6 +31,7 @@ use num::traits::One;
 /// This allows the MedianHeap struct to use the MyMedian struct to calculate the median of the values in the heap when 2 median candidates are found.
 /// 
 /// ```
+/// # #[cfg(feature = "std")] {
 /// use median_heap::{MergeMedian, MedianHeap};
 /// 
 /// struct MyMedian;
@@ -48,6 +52,7 @@ use num::traits::One;
 /// heap.push(4);
 /// 
 /// assert_eq!(3, heap.get_median().unwrap()); // Two median candidates are 2 and 3. MyMedian.merge(2, 3) returns 3.
+/// # }
 /// ```
 pub trait MergeMedian<T> {
   fn merge(&self, a: &T, b: &T) -> T;
@@ -68,7 +73,7 @@ pub trait MergeMedian<T> {
 /// ```
 #[derive(Clone)]
 pub struct LeftHandedMedian;
-impl<T: Ord + Add + Copy> MergeMedian<T> for LeftHandedMedian {
+impl<T: Ord + Copy> MergeMedian<T> for LeftHandedMedian {
   fn merge(&self, a: &T, b: &T) -> T {
       if a < b {
           *a
@@ -84,11 +89,46 @@ impl Default for LeftHandedMedian {
   }
 }
 
+/// RightHandedMedian is a struct that implements the MergeMedian trait.
+/// It calculates the median by taking the larger of the two values, mirroring LeftHandedMedian.
+/// 
+/// Example:
+/// ```
+/// use median_heap::{RightHandedMedian, MergeMedian};
+/// 
+/// let right_handed_median = RightHandedMedian;
+/// let a = 1;
+/// let b = 2;
+/// let median = right_handed_median.merge(&a, &b);
+/// assert_eq!(median, 2);
+/// ```
+#[derive(Clone)]
+pub struct RightHandedMedian;
+impl<T: Ord + Copy> MergeMedian<T> for RightHandedMedian {
+  fn merge(&self, a: &T, b: &T) -> T {
+      if a > b {
+          *a
+      } else {
+          *b
+      }
+  }
+}
+
+impl Default for RightHandedMedian {
+  fn default() -> Self {
+      RightHandedMedian
+  }
+}
+
 /// MidpointMedian is a struct that implements the MergeMedian trait.
 /// It calculates the median by taking the average of the two values.
 /// 
+/// It works for any type with addition, division and a one, including wrappers like AssertOrd, but adding the values first can overflow and integer division truncates towards zero.
+/// IntegerMidpointMedian and FloatMidpointMedian avoid both for integers and floats, so it's deprecated in favour of them.
+/// 
 /// Example:
 /// ```
+/// # #![allow(deprecated)]
 /// use median_heap::{MidpointMedian, MergeMedian};
 /// 
 /// let midpoint_median = MidpointMedian;
@@ -97,16 +137,92 @@ impl Default for LeftHandedMedian {
 /// let median = midpoint_median.merge(&a, &b);
 /// assert_eq!(median, 3);
 /// ```
+#[deprecated(note = "adding the values can overflow, use IntegerMidpointMedian or FloatMidpointMedian instead")]
 #[derive(Clone)]
 pub struct MidpointMedian;
+#[allow(deprecated)]
 impl<T: Div<Output = T> + Add<T, Output = T> + Copy + One> MergeMedian<T> for MidpointMedian {
   fn merge(&self, a: &T, b: &T) -> T {
       (*a + *b) / (T::one() + T::one())
   }
 }
 
+#[allow(deprecated)]
 impl Default for MidpointMedian {
   fn default() -> Self {
       MidpointMedian
   }
 }
+
+/// IntegerMidpointMedian is a struct that implements the MergeMedian trait for integers.
+/// It calculates the median by taking the average of the two values rounded down, towards negative infinity, without overflowing.
+/// It only needs num::integer::Average, which num implements for every integer type including big integers.
+/// 
+/// Example:
+/// ```
+/// use median_heap::{IntegerMidpointMedian, MergeMedian};
+/// 
+/// let midpoint_median = IntegerMidpointMedian;
+/// assert_eq!(-2, midpoint_median.merge(&-3, &0));
+/// assert_eq!(i32::MAX - 1, midpoint_median.merge(&(i32::MAX - 1), &i32::MAX));
+/// ```
+#[derive(Clone)]
+pub struct IntegerMidpointMedian;
+impl<T: Average> MergeMedian<T> for IntegerMidpointMedian {
+  fn merge(&self, a: &T, b: &T) -> T {
+      a.average_floor(b)
+  }
+}
+
+impl Default for IntegerMidpointMedian {
+  fn default() -> Self {
+      IntegerMidpointMedian
+  }
+}
+
+/// FloatMidpointMedian is a struct that implements the MergeMedian trait for floating point numbers.
+/// It calculates the median by taking the average of the two values, halving both first so values near the edges of the range can't overflow to infinity.
+/// 
+/// Floats don't implement Ord, so the values have to be wrapped in AssertOrd to be pushed to a MedianHeap, which this median kind also supports.
+/// It's only implemented with the std feature, since the Float trait requires it.
+/// 
+/// Example:
+/// ```
+/// # #[cfg(feature = "std")] {
+/// use median_heap::{FloatMidpointMedian, MergeMedian};
+/// 
+/// let midpoint_median = FloatMidpointMedian;
+/// assert_eq!(2.5, midpoint_median.merge(&2.0, &3.0));
+/// assert_eq!(f64::MAX, midpoint_median.merge(&f64::MAX, &f64::MAX));
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct FloatMidpointMedian;
+#[cfg(feature = "std")]
+impl<T: Float> MergeMedian<T> for FloatMidpointMedian {
+  fn merge(&self, a: &T, b: &T) -> T {
+      let two = T::one() + T::one();
+      *a / two + *b / two
+  }
+}
+
+impl Default for FloatMidpointMedian {
+  fn default() -> Self {
+      FloatMidpointMedian
+  }
+}
+
+/// SaturatingMidpointMedian is an alias of IntegerMidpointMedian for code looking for a midpoint that can't overflow.
+/// IntegerMidpointMedian already takes the average rounded down without overflowing or saturating, so small integer types like u8 need no separate median kind.
+/// 
+/// Example:
+/// ```
+/// use median_heap::{SaturatingMidpointMedian, MergeMedian};
+/// 
+/// let midpoint_median = SaturatingMidpointMedian::default();
+/// let a: u8 = 200;
+/// let b: u8 = 250;
+/// let median = midpoint_median.merge(&a, &b);
+/// assert_eq!(median, 225);
+/// ```
+pub type SaturatingMidpointMedian = IntegerMidpointMedian;
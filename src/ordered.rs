@@ -0,0 +1,563 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, VecDeque};
+use std::ops::Sub;
+
+use crate::medianheap::{compare_distances, DistanceComparator};
+use crate::{EvictionPolicy, MedianHeapError, MergeMedian};
+
+/// OrderedMedianHeap is a struct that keeps track of the median like MedianHeap, but stores both halves in ordered multisets instead of binary heaps.
+/// Each half is a BTreeMap counting the occurrences of every value, so deleting a value takes O(logn) instead of the O(n) of MedianHeap.
+/// It's intended for delete-heavy workloads such as running windows, where every push is paired with a delete.
+///
+/// It covers the operations a running window needs, with the same semantics and median kinds as MedianHeap:
+/// push, pop, replace_median, delete, try_delete, contains, get_median, min, max, peak_max, peak_min, len, len_lower, len_upper, iter, iter_lower, iter_upper, clear, Extend and FromIterator.
+/// A window is bounded with with_policy or with_farthest_eviction.
+/// The rest of the surface of MedianHeap, such as the quantiles, the statistics and the observers, isn't available.
+///
+/// Example:
+/// ```
+/// use median_heap::{IntegerMidpointMedian, OrderedMedianHeap};
+///
+/// let mut heap = OrderedMedianHeap::new(IntegerMidpointMedian);
+/// heap.push(2);
+/// heap.push(4);
+/// heap.push(6);
+/// heap.push(8);
+///
+/// assert_eq!(5, heap.get_median().unwrap());
+///
+/// heap.delete(&2);
+/// assert_eq!(6, heap.get_median().unwrap());
+/// ```
+#[derive(Clone, Debug)]
+pub struct OrderedMedianHeap<T, K> {
+  median_kind: K,
+  // The lower half, holding at most one value more than the upper half and never fewer.
+  lower: BTreeMap<T, usize>,
+  upper: BTreeMap<T, usize>,
+  lower_len: usize,
+  upper_len: usize,
+  // The capacity and eviction policy of a bounded heap, None if the heap is unbounded.
+  bound: Option<Bound<T>>,
+}
+
+// The capacity of a bounded OrderedMedianHeap and the state its eviction policy needs.
+#[derive(Clone, Debug)]
+struct Bound<T> {
+  capacity: usize,
+  policy: EvictionPolicy,
+  // The pushed values in chronological order, only kept for EvictOldest.
+  history: VecDeque<T>,
+  // The number of copies of every value that were removed since they were pushed, only kept for EvictOldest.
+  // Equal values are interchangeable, so every removed copy makes the oldest history entry of its value stale.
+  stale: BTreeMap<T, usize>,
+  // Only set for heaps created with with_farthest_eviction, since measuring distances requires subtraction.
+  compare_distances: Option<DistanceComparator<T>>,
+}
+
+impl<T: Ord> Bound<T> {
+  // Drops a stale copy of the value, returning false if the value has none.
+  fn drop_stale(&mut self, value: &T) -> bool {
+    match self.stale.get_mut(value) {
+      Some(count) if *count > 1 => *count -= 1,
+      Some(_) => {
+        self.stale.remove(value);
+      }
+      None => return false,
+    }
+    true
+  }
+}
+
+impl<T: Ord, K: Default> Default for OrderedMedianHeap<T, K> {
+  fn default() -> Self {
+    OrderedMedianHeap {
+      median_kind: K::default(),
+      lower: BTreeMap::new(),
+      upper: BTreeMap::new(),
+      lower_len: 0,
+      upper_len: 0,
+      bound: None,
+    }
+  }
+}
+
+impl<T: Ord + Copy, K: MergeMedian<T>> OrderedMedianHeap<T, K> {
+  /// Creates a new OrderedMedianHeap instance with the specified median kind.
+  pub fn new(median_kind: K) -> Self {
+    OrderedMedianHeap {
+      median_kind,
+      lower: BTreeMap::new(),
+      upper: BTreeMap::new(),
+      lower_len: 0,
+      upper_len: 0,
+      bound: None,
+    }
+  }
+
+  /// Creates a new OrderedMedianHeap instance that holds at most capacity values.
+  /// When a push would exceed the capacity, the specified policy decides which value is evicted, in the same way as for MedianHeap::with_policy.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{EvictionPolicy, IntegerMidpointMedian, OrderedMedianHeap};
+  ///
+  /// let mut window = OrderedMedianHeap::with_policy(IntegerMidpointMedian, 3, EvictionPolicy::EvictOldest);
+  /// window.extend([1, 2, 3, 10]);
+  ///
+  /// assert_eq!(3, window.len());
+  /// assert!(!window.contains(&1));
+  /// assert_eq!(3, window.get_median().unwrap());
+  /// ```
+  ///
+  /// # Panics
+  /// Panics if capacity is 0 or policy is EvictionPolicy::EvictFarthestFromMedian.
+  pub fn with_policy(median_kind: K, capacity: usize, policy: EvictionPolicy) -> Self {
+    OrderedMedianHeap::bounded(median_kind, capacity, policy, None)
+  }
+
+  /// Creates a new OrderedMedianHeap instance that holds at most capacity values and evicts with EvictionPolicy::EvictFarthestFromMedian.
+  /// Measuring the distances subtracts values, which is why this policy has its own constructor.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{IntegerMidpointMedian, OrderedMedianHeap};
+  ///
+  /// let mut heap = OrderedMedianHeap::with_farthest_eviction(IntegerMidpointMedian, 3);
+  /// heap.extend([5, 6, 7, 100]);
+  ///
+  /// assert_eq!(3, heap.len());
+  /// assert!(!heap.contains(&100));
+  /// ```
+  ///
+  /// # Panics
+  /// Panics if capacity is 0.
+  pub fn with_farthest_eviction(median_kind: K, capacity: usize) -> Self
+  where
+    T: Sub<Output = T>,
+  {
+    OrderedMedianHeap::bounded(median_kind, capacity, EvictionPolicy::EvictFarthestFromMedian, Some(compare_distances))
+  }
+
+  fn bounded(median_kind: K, capacity: usize, policy: EvictionPolicy, compare_distances: Option<DistanceComparator<T>>) -> Self {
+    assert!(capacity > 0, "capacity must be greater than 0");
+    assert!(
+      policy != EvictionPolicy::EvictFarthestFromMedian || compare_distances.is_some(),
+      "EvictFarthestFromMedian subtracts values, create the heap with OrderedMedianHeap::with_farthest_eviction"
+    );
+    let mut heap = OrderedMedianHeap::new(median_kind);
+    heap.bound = Some(Bound {
+      capacity,
+      policy,
+      history: VecDeque::new(),
+      stale: BTreeMap::new(),
+      compare_distances,
+    });
+    heap
+  }
+
+  /// Creates a new, empty OrderedMedianHeap instance using the default value of the median kind.
+  pub fn empty() -> Self
+  where
+    K: Default,
+  {
+    Self::default()
+  }
+
+  /// Returns the median of the values in the heap.
+  /// If the heap is empty, the method returns None.
+  ///
+  /// # Complexity
+  /// O(logn)
+  pub fn get_median(&self) -> Option<T> {
+    let left = self.peak_max()?;
+    if self.lower_len == self.upper_len {
+      let right = self.peak_min().unwrap();
+      Some(self.median_kind.merge(left, right))
+    } else {
+      Some(*left)
+    }
+  }
+
+  /// Adds a value to the heap.
+  /// If the heap is bounded and the push exceeds its capacity, a value is evicted according to its policy.
+  ///
+  /// # Complexity
+  /// O(logn) amortized
+  pub fn push(&mut self, value: T) {
+    // Only the eviction policies need to know on which side of the median the value was added.
+    let above_median = self.bound.is_some() && self.get_median().is_some_and(|median| value > median);
+
+    if self.peak_max().is_none_or(|root| value <= *root) {
+      insert(&mut self.lower, value);
+      self.lower_len += 1;
+    } else {
+      insert(&mut self.upper, value);
+      self.upper_len += 1;
+    }
+    self.rebalance();
+
+    if self.bound.is_some() {
+      self.evict(value, above_median);
+    }
+  }
+
+  /// Removes the median of the values in the heap and returns it.
+  /// If two median candidates are found, both are removed and merged into the returned median.
+  /// If the heap is empty, the method returns None.
+  ///
+  /// # Complexity
+  /// O(logn)
+  pub fn pop(&mut self) -> Option<T> {
+    let balanced = self.lower_len == self.upper_len;
+    let left = remove_last(&mut self.lower)?;
+    self.lower_len -= 1;
+    self.track_removed(left, 1);
+    if balanced {
+      let right = remove_first(&mut self.upper).unwrap();
+      self.upper_len -= 1;
+      self.track_removed(right, 1);
+      return Some(self.median_kind.merge(&left, &right))
+    }
+
+    self.rebalance();
+    Some(left)
+  }
+
+  /// Removes the median of the values in the heap, adds the specified value and returns the removed median.
+  /// The median is removed in the same way as pop does, so if two median candidates are found both are removed.
+  /// If the heap is empty, the value is added and the method returns None.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{LeftHandedMedian, OrderedMedianHeap};
+  ///
+  /// let mut heap: OrderedMedianHeap<i32, LeftHandedMedian> = vec![1, 2, 3].into_iter().collect();
+  ///
+  /// assert_eq!(Some(2), heap.replace_median(10));
+  /// assert_eq!(3, heap.len());
+  /// assert_eq!(3, heap.get_median().unwrap());
+  /// ```
+  ///
+  /// # Complexity
+  /// O(logn) amortized
+  pub fn replace_median(&mut self, value: T) -> Option<T> {
+    let median = self.pop();
+    self.push(value);
+    median
+  }
+
+  /// Removes every instance of the specified value from the heap.
+  ///
+  /// # Complexity
+  /// O(logn)
+  pub fn delete(&mut self, value: &T) {
+    if let Some(count) = self.lower.remove(value) {
+      self.lower_len -= count;
+      self.track_removed(*value, count);
+    }
+    if let Some(count) = self.upper.remove(value) {
+      self.upper_len -= count;
+      self.track_removed(*value, count);
+    }
+    self.rebalance();
+  }
+
+  /// Removes a single instance of the specified value from the heap and returns it.
+  /// Unlike delete, this method reports why the removal failed.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{LeftHandedMedian, MedianHeapError, OrderedMedianHeap};
+  ///
+  /// let mut heap = OrderedMedianHeap::new(LeftHandedMedian);
+  /// assert_eq!(Err(MedianHeapError::Empty), heap.try_delete(&1));
+  ///
+  /// heap.push(1);
+  /// heap.push(3);
+  /// heap.push(3);
+  ///
+  /// assert_eq!(Ok(3), heap.try_delete(&3));
+  /// assert_eq!(2, heap.len());
+  /// assert_eq!(Err(MedianHeapError::NotFound), heap.try_delete(&2));
+  /// ```
+  ///
+  /// # Complexity
+  /// O(logn)
+  pub fn try_delete(&mut self, value: &T) -> Result<T, MedianHeapError> {
+    if self.is_empty() {
+      return Err(MedianHeapError::Empty)
+    }
+    if !self.remove_one(value) {
+      return Err(MedianHeapError::NotFound)
+    }
+    Ok(*value)
+  }
+
+  /// Returns true if the heap contains the specified value, false otherwise.
+  ///
+  /// # Complexity
+  /// O(logn)
+  pub fn contains(&self, value: &T) -> bool {
+    self.lower.contains_key(value) || self.upper.contains_key(value)
+  }
+
+  /// Returns the largest value of the lower half of the heap.
+  pub fn peak_max(&self) -> Option<&T> {
+    self.lower.keys().next_back()
+  }
+
+  /// Returns the smallest value of the upper half of the heap.
+  pub fn peak_min(&self) -> Option<&T> {
+    self.upper.keys().next()
+  }
+
+  // Removes a single instance of the value and rebalances the heap, returning false if the value isn't found.
+  fn remove_one(&mut self, value: &T) -> bool {
+    if remove(&mut self.lower, value) {
+      self.lower_len -= 1;
+    } else if remove(&mut self.upper, value) {
+      self.upper_len -= 1;
+    } else {
+      return false
+    }
+
+    self.track_removed(*value, 1);
+    self.rebalance();
+    true
+  }
+
+  // Records the pushed value for a bounded heap and evicts a value according to its policy if the capacity is exceeded.
+  fn evict(&mut self, value: T, above_median: bool) {
+    let bound = self.bound.as_mut().unwrap();
+    let (capacity, policy) = (bound.capacity, bound.policy);
+    if policy == EvictionPolicy::EvictOldest {
+      bound.history.push_back(value);
+    }
+
+    let history_len = bound.history.len();
+    if self.len() <= capacity {
+      // Drop history entries of values that were removed by other methods, so it can't grow without bound.
+      if history_len > 2 * capacity {
+        let bound = self.bound.as_mut().unwrap();
+        let mut history = std::mem::take(&mut bound.history);
+        history.retain(|value| !bound.drop_stale(value));
+        bound.history = history;
+      }
+      return
+    }
+
+    let evicted = match policy {
+      EvictionPolicy::EvictOldest => self.oldest(),
+      EvictionPolicy::EvictFarthestFromMedian => self.farthest_from_median(),
+      EvictionPolicy::EvictExtreme if above_median => *self.max().unwrap(),
+      EvictionPolicy::EvictExtreme => *self.min().unwrap(),
+    };
+    self.remove_one(&evicted);
+  }
+
+  // Returns the oldest value that is still stored in the heap, dropping the stale history entries in front of it.
+  // Its entry stays at the front, since evicting the value marks it stale like any other removal, so the next call drops it.
+  fn oldest(&mut self) -> T {
+    let bound = self.bound.as_mut().unwrap();
+    loop {
+      let oldest = *bound.history.front().unwrap();
+      if !bound.drop_stale(&oldest) {
+        return oldest
+      }
+      bound.history.pop_front();
+    }
+  }
+
+  // Returns the smallest or the largest value, whichever is farther from the median, preferring the smallest one on a tie.
+  fn farthest_from_median(&self) -> T {
+    let (min, max) = (*self.min().unwrap(), *self.max().unwrap());
+    let median = self.get_median().unwrap();
+    let compare_distances = self.bound.as_ref().and_then(|bound| bound.compare_distances);
+    if compare_distances.is_some_and(|compare_distances| compare_distances(&min, &median, &max) == Ordering::Less) {
+      max
+    } else {
+      min
+    }
+  }
+
+  // Records removed copies of a value for a heap bounded with EvictOldest, so their history entries are skipped.
+  fn track_removed(&mut self, value: T, count: usize) {
+    if let Some(bound) = self.bound.as_mut().filter(|bound| bound.policy == EvictionPolicy::EvictOldest) {
+      *bound.stale.entry(value).or_insert(0) += count;
+    }
+  }
+
+  // Moves values between the halves until the lower half holds at most one value more than the upper half, and never fewer.
+  fn rebalance(&mut self) {
+    while self.lower_len > self.upper_len + 1 {
+      let value = remove_last(&mut self.lower).unwrap();
+      insert(&mut self.upper, value);
+      self.lower_len -= 1;
+      self.upper_len += 1;
+    }
+    while self.upper_len > self.lower_len {
+      let value = remove_first(&mut self.upper).unwrap();
+      insert(&mut self.lower, value);
+      self.upper_len -= 1;
+      self.lower_len += 1;
+    }
+  }
+}
+
+impl<T, K> OrderedMedianHeap<T, K> {
+  /// Returns the number of values in the heap.
+  ///
+  /// # Complexity
+  /// O(1)
+  pub fn len(&self) -> usize {
+    self.lower_len + self.upper_len
+  }
+
+  /// Returns the number of values in the lower half of the heap, which holds the median.
+  ///
+  /// # Complexity
+  /// O(1)
+  pub fn len_lower(&self) -> usize {
+    self.lower_len
+  }
+
+  /// Returns the number of values in the upper half of the heap.
+  ///
+  /// # Complexity
+  /// O(1)
+  pub fn len_upper(&self) -> usize {
+    self.upper_len
+  }
+
+  /// Returns true if the heap is empty, false otherwise.
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+
+  /// Returns the smallest value in the heap.
+  /// If the heap is empty, the method returns None.
+  ///
+  /// # Complexity
+  /// O(logn)
+  pub fn min(&self) -> Option<&T> {
+    self.lower.keys().next()
+  }
+
+  /// Returns the largest value in the heap.
+  /// If the heap is empty, the method returns None.
+  ///
+  /// # Complexity
+  /// O(logn)
+  pub fn max(&self) -> Option<&T> {
+    self.upper.keys().next_back().or_else(|| self.lower.keys().next_back())
+  }
+
+  /// Returns an iterator over references to the values in the heap in ascending order.
+  /// Unlike MedianHeap::iter, the values are sorted, since both halves are ordered.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::{LeftHandedMedian, OrderedMedianHeap};
+  ///
+  /// let heap: OrderedMedianHeap<i32, LeftHandedMedian> = vec![3, 1, 2, 1].into_iter().collect();
+  ///
+  /// assert_eq!(vec![&1, &1, &2, &3], heap.iter().collect::<Vec<_>>());
+  /// ```
+  pub fn iter(&self) -> impl Iterator<Item = &T> {
+    self.iter_lower().chain(self.iter_upper())
+  }
+
+  /// Returns an iterator over references to the values in the lower half of the heap in ascending order.
+  pub fn iter_lower(&self) -> impl Iterator<Item = &T> {
+    expand(&self.lower)
+  }
+
+  /// Returns an iterator over references to the values in the upper half of the heap in ascending order.
+  pub fn iter_upper(&self) -> impl Iterator<Item = &T> {
+    expand(&self.upper)
+  }
+
+  /// Removes all values from the heap.
+  /// A bounded heap keeps its capacity and policy.
+  pub fn clear(&mut self) {
+    self.lower.clear();
+    self.upper.clear();
+    self.lower_len = 0;
+    self.upper_len = 0;
+    if let Some(bound) = self.bound.as_mut() {
+      bound.history.clear();
+      bound.stale.clear();
+    }
+  }
+
+  /// Returns a reference to the median kind used by the heap.
+  pub fn median_kind(&self) -> &K {
+    &self.median_kind
+  }
+}
+
+impl<T: Ord + Copy, K: MergeMedian<T> + Default> FromIterator<T> for OrderedMedianHeap<T, K> {
+  fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+    let mut heap = OrderedMedianHeap::empty();
+    for value in iter {
+      heap.push(value);
+    }
+    heap
+  }
+}
+
+impl<T: Ord + Copy, K: MergeMedian<T>> Extend<T> for OrderedMedianHeap<T, K> {
+  /// Adds every value of the iterator to the heap in the same way as push.
+  fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+    for value in iter {
+      self.push(value);
+    }
+  }
+}
+
+// Yields every value of the multiset as often as it occurs, in ascending order.
+fn expand<T>(map: &BTreeMap<T, usize>) -> impl Iterator<Item = &T> {
+  map.iter().flat_map(|(value, &count)| std::iter::repeat_n(value, count))
+}
+
+// Adds one occurrence of the value to the multiset.
+fn insert<T: Ord>(map: &mut BTreeMap<T, usize>, value: T) {
+  *map.entry(value).or_insert(0) += 1;
+}
+
+// Removes one occurrence of the value from the multiset, returning false if it isn't present.
+fn remove<T: Ord>(map: &mut BTreeMap<T, usize>, value: &T) -> bool {
+  match map.get_mut(value) {
+    Some(count) if *count > 1 => *count -= 1,
+    Some(_) => {
+      map.remove(value);
+    }
+    None => return false,
+  }
+  true
+}
+
+// Removes one occurrence of the smallest value from the multiset.
+fn remove_first<T: Ord + Copy>(map: &mut BTreeMap<T, usize>) -> Option<T> {
+  let mut entry = map.first_entry()?;
+  let value = *entry.key();
+  if *entry.get() > 1 {
+    *entry.get_mut() -= 1;
+  } else {
+    entry.remove();
+  }
+  Some(value)
+}
+
+// Removes one occurrence of the largest value from the multiset.
+fn remove_last<T: Ord + Copy>(map: &mut BTreeMap<T, usize>) -> Option<T> {
+  let mut entry = map.last_entry()?;
+  let value = *entry.key();
+  if *entry.get() > 1 {
+    *entry.get_mut() -= 1;
+  } else {
+    entry.remove();
+  }
+  Some(value)
+}
@@ -0,0 +1,121 @@
+use core::cmp::Ordering;
+use core::ops::{Add, Div, Mul, Sub};
+#[cfg(feature = "std")]
+use num::traits::Float;
+use num::traits::One;
+
+#[cfg(feature = "std")]
+use crate::{FloatMidpointMedian, MergeMedian};
+
+/// AssertOrd is a wrapper that implements Ord for a type that only implements PartialOrd, such as f64.
+/// It allows such values to be stored in a MedianHeap, as long as no two values pushed to the same heap are incomparable.
+///
+/// Every comparison unwraps the result of partial_cmp.
+///
+/// # Panics
+/// Comparing two incomparable values, for example when one of them is NaN, panics.
+/// Use AssertOrd::try_new to reject values that aren't comparable to themselves before they're pushed.
+///
+/// FloatMidpointMedian merges wrapped floats, and the wrapper forwards arithmetic to the wrapped type for the eviction policies and custom median kinds.
+///
+/// Example:
+/// ```
+/// # #[cfg(feature = "std")] {
+/// use median_heap::{AssertOrd, MedianHeap, FloatMidpointMedian};
+///
+/// let mut heap = MedianHeap::new(FloatMidpointMedian);
+/// heap.push(AssertOrd(1.5));
+/// heap.push(AssertOrd(2.5));
+///
+/// assert_eq!(2.0, heap.get_median().unwrap().into_inner());
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AssertOrd<T>(pub T);
+
+impl<T: PartialOrd> AssertOrd<T> {
+  /// Wraps the value, or returns None if the value isn't comparable to itself, like NaN.
+  ///
+  /// Example:
+  /// ```
+  /// use median_heap::AssertOrd;
+  ///
+  /// assert!(AssertOrd::try_new(1.0).is_some());
+  /// assert!(AssertOrd::try_new(f64::NAN).is_none());
+  /// ```
+  pub fn try_new(value: T) -> Option<Self> {
+    value.partial_cmp(&value).map(|_| AssertOrd(value))
+  }
+}
+
+impl<T> AssertOrd<T> {
+  /// Returns the wrapped value.
+  pub fn into_inner(self) -> T {
+    self.0
+  }
+}
+
+impl<T: PartialOrd> PartialEq for AssertOrd<T> {
+  fn eq(&self, other: &Self) -> bool {
+    self.cmp(other) == Ordering::Equal
+  }
+}
+
+impl<T: PartialOrd> Eq for AssertOrd<T> {}
+
+impl<T: PartialOrd> PartialOrd for AssertOrd<T> {
+  fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+    Some(self.cmp(other))
+  }
+}
+
+impl<T: PartialOrd> Ord for AssertOrd<T> {
+  fn cmp(&self, other: &Self) -> Ordering {
+    self.0.partial_cmp(&other.0).expect("AssertOrd compared two incomparable values")
+  }
+}
+
+impl<T: Add<Output = T>> Add for AssertOrd<T> {
+  type Output = Self;
+
+  fn add(self, other: Self) -> Self {
+    AssertOrd(self.0 + other.0)
+  }
+}
+
+impl<T: Sub<Output = T>> Sub for AssertOrd<T> {
+  type Output = Self;
+
+  fn sub(self, other: Self) -> Self {
+    AssertOrd(self.0 - other.0)
+  }
+}
+
+impl<T: Mul<Output = T>> Mul for AssertOrd<T> {
+  type Output = Self;
+
+  fn mul(self, other: Self) -> Self {
+    AssertOrd(self.0 * other.0)
+  }
+}
+
+impl<T: Div<Output = T>> Div for AssertOrd<T> {
+  type Output = Self;
+
+  fn div(self, other: Self) -> Self {
+    AssertOrd(self.0 / other.0)
+  }
+}
+
+impl<T: One> One for AssertOrd<T> {
+  fn one() -> Self {
+    AssertOrd(T::one())
+  }
+}
+
+#[cfg(feature = "std")]
+impl<T: Float> MergeMedian<AssertOrd<T>> for FloatMidpointMedian {
+  fn merge(&self, a: &AssertOrd<T>, b: &AssertOrd<T>) -> AssertOrd<T> {
+    AssertOrd(self.merge(&a.0, &b.0))
+  }
+}
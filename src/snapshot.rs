@@ -0,0 +1,62 @@
+use crate::{MedianHeap, MergeMedian};
+
+/// MedianSnapshot is a struct that holds the statistics of a MedianHeap at a single point in time.
+/// It's immutable and doesn't borrow the heap, so it can be shared between threads while the heap keeps changing.
+/// MedianHeap::snapshot wraps it in an Arc, which makes sharing it as cheap as cloning a pointer.
+///
+/// Example:
+/// ```
+/// use std::thread;
+/// use median_heap::{MedianHeap, IntegerMidpointMedian};
+///
+/// let mut heap: MedianHeap<i32, IntegerMidpointMedian> = (1..=9).collect();
+/// let snapshot = heap.snapshot();
+/// heap.push(100);
+///
+/// let reader = {
+///   let snapshot = snapshot.clone();
+///   thread::spawn(move || snapshot.median)
+/// };
+///
+/// assert_eq!(Some(5), reader.join().unwrap());
+/// assert_eq!(9, snapshot.len);
+/// assert_eq!(Some(9), snapshot.max);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MedianSnapshot<T> {
+  /// The median of the values, see MedianHeap::get_median.
+  pub median: Option<T>,
+  /// The number of values.
+  pub len: usize,
+  /// The smallest value.
+  pub min: Option<T>,
+  /// The largest value.
+  pub max: Option<T>,
+  /// The first quartile of the values, see MedianHeap::q1.
+  pub q1: Option<T>,
+  /// The third quartile of the values, see MedianHeap::q3.
+  pub q3: Option<T>,
+}
+
+impl<T: Ord + Copy> MedianSnapshot<T> {
+  /// Creates a new MedianSnapshot instance holding the current statistics of the heap.
+  ///
+  /// # Complexity
+  /// O(n)
+  pub fn new<K: MergeMedian<T>>(heap: &MedianHeap<T, K>) -> Self {
+    MedianSnapshot {
+      median: heap.get_median(),
+      len: heap.len(),
+      min: heap.min().copied(),
+      max: heap.max().copied(),
+      q1: heap.q1(),
+      q3: heap.q3(),
+    }
+  }
+}
+
+impl<T: Ord + Copy, K: MergeMedian<T>> From<&MedianHeap<T, K>> for MedianSnapshot<T> {
+  fn from(heap: &MedianHeap<T, K>) -> Self {
+    MedianSnapshot::new(heap)
+  }
+}
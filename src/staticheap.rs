@@ -0,0 +1,113 @@
+use crate::MergeMedian;
+
+/// StaticMedianHeap is a struct that keeps track of the median of the N most recently pushed values without allocating.
+/// Its capacity is fixed at compile time and both the values and their push order are stored in arrays, so it's available without the std feature, for embedded targets without an allocator.
+///
+/// It shares the two-halves concept of MedianHeap, but instead of two binary heaps it keeps a single sorted array.
+/// The lower half is the first ceil(len / 2) values of that array and the upper half the rest, so the median candidates sit in the middle of it.
+/// Pushing a value inserts it into place by shifting the values above it, which is fast for the small capacities arrays are used for.
+///
+/// Once N values are held, every push evicts the oldest value, like a MedianHeap bounded by EvictionPolicy::EvictOldest.
+///
+/// Example:
+/// ```
+/// use median_heap::{IntegerMidpointMedian, StaticMedianHeap};
+///
+/// let mut heap: StaticMedianHeap<i32, IntegerMidpointMedian, 3> = StaticMedianHeap::new(IntegerMidpointMedian);
+/// heap.push(1);
+/// heap.push(8);
+/// assert_eq!(4, heap.get_median().unwrap());
+///
+/// heap.push(3);
+/// heap.push(5);
+///
+/// assert_eq!(3, heap.len());
+/// assert_eq!(5, heap.get_median().unwrap());
+/// ```
+#[derive(Clone, Debug)]
+pub struct StaticMedianHeap<T, K, const N: usize> {
+  median_kind: K,
+  // The values in ascending order, of which only the first len are in use.
+  sorted: [T; N],
+  // The values in the order they were pushed, as a ring buffer starting at oldest once it's full.
+  history: [T; N],
+  oldest: usize,
+  len: usize,
+}
+
+impl<T: Ord + Copy + Default, K: MergeMedian<T>, const N: usize> StaticMedianHeap<T, K, N> {
+  /// Creates a new, empty StaticMedianHeap instance with the specified median kind.
+  /// A capacity of 0 fails to compile.
+  pub fn new(median_kind: K) -> Self {
+    const { assert!(N > 0, "the capacity of a StaticMedianHeap must be greater than 0") };
+    StaticMedianHeap {
+      median_kind,
+      sorted: [T::default(); N],
+      history: [T::default(); N],
+      oldest: 0,
+      len: 0,
+    }
+  }
+
+  /// Returns the median of the values in the heap.
+  /// If the heap is empty, the method returns None.
+  ///
+  /// # Complexity
+  /// O(1)
+  pub fn get_median(&self) -> Option<T> {
+    if self.len == 0 {
+      return None
+    }
+
+    let middle = (self.len - 1) / 2;
+    if self.len.is_multiple_of(2) {
+      Some(self.median_kind.merge(&self.sorted[middle], &self.sorted[middle + 1]))
+    } else {
+      Some(self.sorted[middle])
+    }
+  }
+
+  /// Adds a value to the heap, evicting the oldest value if the heap already holds N values.
+  ///
+  /// # Complexity
+  /// O(N)
+  pub fn push(&mut self, value: T) {
+    if self.len == N {
+      let evicted = core::mem::replace(&mut self.history[self.oldest], value);
+      self.oldest = (self.oldest + 1) % N;
+      let index = self.sorted[..self.len].binary_search(&evicted).unwrap();
+      self.sorted.copy_within(index + 1..self.len, index);
+      self.len -= 1;
+    } else {
+      self.history[self.len] = value;
+    }
+
+    let index = self.sorted[..self.len].partition_point(|x| *x <= value);
+    self.sorted.copy_within(index..self.len, index + 1);
+    self.sorted[index] = value;
+    self.len += 1;
+  }
+}
+
+impl<T, K, const N: usize> StaticMedianHeap<T, K, N> {
+  /// Returns the number of values in the heap.
+  pub fn len(&self) -> usize {
+    self.len
+  }
+
+  /// Returns true if the heap is empty, false otherwise.
+  pub fn is_empty(&self) -> bool {
+    self.len == 0
+  }
+
+  /// Returns the maximum number of values the heap holds, which is N.
+  pub fn capacity(&self) -> usize {
+    N
+  }
+
+  /// Removes all values from the heap.
+  pub fn clear(&mut self) {
+    self.oldest = 0;
+    self.len = 0;
+  }
+}
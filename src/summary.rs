@@ -0,0 +1,35 @@
+/// MedianSummary is a struct that holds the statistics of a MedianHeap that are commonly exported as metrics.
+/// MedianHeap::summary computes all of them from a single sorted copy of the values, instead of scanning the heap once per statistic.
+///
+/// The percentiles are the values at index floor(p * (n - 1)) in ascending order, like MedianHeap::q1 and MedianHeap::q3, so they're always values stored in the heap.
+///
+/// Example:
+/// ```
+/// use median_heap::{MedianHeap, IntegerMidpointMedian};
+///
+/// let heap: MedianHeap<u32, IntegerMidpointMedian> = (1..=100).collect();
+/// let summary = heap.summary();
+///
+/// assert_eq!(100, summary.count);
+/// assert_eq!(Some(50), summary.median);
+/// assert_eq!(Some(50.5), summary.mean);
+/// assert_eq!(Some(90), summary.p90);
+/// assert_eq!(Some(99), summary.p99);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct MedianSummary<T> {
+  /// The number of values.
+  pub count: usize,
+  /// The median of the values, see MedianHeap::get_median.
+  pub median: Option<T>,
+  /// The smallest value.
+  pub min: Option<T>,
+  /// The largest value.
+  pub max: Option<T>,
+  /// The arithmetic mean of the values, see MedianHeap::checked_mean.
+  pub mean: Option<f64>,
+  /// The 90th percentile of the values.
+  pub p90: Option<T>,
+  /// The 99th percentile of the values.
+  pub p99: Option<T>,
+}
@@ -0,0 +1,53 @@
+//! Helpers for testing code built on MedianHeap, compiled only with the testing feature.
+//! Enable it for dev-dependencies only, so it stays out of regular builds:
+//!
+//! ```toml
+//! [dev-dependencies]
+//! median-heap = { version = "0.1", features = ["testing"] }
+//! ```
+
+use std::fmt::Debug;
+
+use crate::{IntegerMidpointMedian, MedianHeap, MergeMedian};
+
+/// Creates a MedianHeap with the IntegerMidpointMedian median kind holding a copy of the values.
+///
+/// Example:
+/// ```
+/// use median_heap::testing::heap_from;
+///
+/// let heap = heap_from(&[1, 2, 3, 4]);
+///
+/// assert_eq!(4, heap.len());
+/// assert_eq!(2, heap.get_median().unwrap());
+/// ```
+pub fn heap_from<T>(values: &[T]) -> MedianHeap<T, IntegerMidpointMedian>
+where
+  T: Ord + Copy,
+  IntegerMidpointMedian: MergeMedian<T>,
+{
+  MedianHeap::from_iter_with(IntegerMidpointMedian, values.iter().copied())
+}
+
+/// Asserts that the median of the heap equals the expected value.
+/// On failure, the panic message lists the values of the heap in ascending order and points at the caller.
+/// An empty heap has no median, so it always fails.
+///
+/// Example:
+/// ```
+/// use median_heap::testing::{assert_median_eq, heap_from};
+///
+/// let mut heap = heap_from(&[5, 1, 3]);
+/// assert_median_eq(&heap, 3);
+///
+/// heap.push(7);
+/// assert_median_eq(&heap, 4);
+/// ```
+#[track_caller]
+pub fn assert_median_eq<T, K>(heap: &MedianHeap<T, K>, expected: T)
+where
+  T: Ord + Copy + Debug,
+  K: MergeMedian<T>,
+{
+  assert_eq!(Some(expected), heap.get_median(), "unexpected median of the values {:?}", heap.to_sorted_vec());
+}
@@ -11,16 +11,75 @@ fn test_left_handed_median() {
 
 #[test]
 fn test_right_handed_median() {
-    let right_handed_median = MidpointMedian;
+    let right_handed_median = RightHandedMedian;
+    let a = 1;
+    let b = 2;
+    let median = right_handed_median.merge(&a, &b);
+    assert_eq!(median, 2);
+    assert_eq!(right_handed_median.merge(&b, &a), 2);
+
+    let mut heap = MedianHeap::<i32, RightHandedMedian>::empty();
+    heap.push(1);
+    heap.push(2);
+    assert_eq!(heap.get_median(), Some(2));
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_midpoint_median() {
+    let midpoint_median = MidpointMedian;
     let a = 2;
     let b = 4;
-    let median = right_handed_median.merge(&a, &b);
+    let median = midpoint_median.merge(&a, &b);
     assert_eq!(median, 3);
+    assert_eq!(AssertOrd(1.25), midpoint_median.merge(&AssertOrd(0.5), &AssertOrd(2.0)));
+}
+
+#[test]
+fn test_saturating_midpoint_median() {
+    let saturating_midpoint_median = SaturatingMidpointMedian::default();
+    assert_eq!(225u8, saturating_midpoint_median.merge(&200, &250));
+    assert_eq!(254u8, saturating_midpoint_median.merge(&255, &254));
+    assert_eq!(0u8, saturating_midpoint_median.merge(&0, &1));
+    assert_eq!(-2i8, saturating_midpoint_median.merge(&-128, &124));
+
+    let mut heap = MedianHeap::new(SaturatingMidpointMedian::default());
+    heap.push(250u8);
+    heap.push(200u8);
+    assert_eq!(225, heap.get_median().unwrap());
+}
+
+#[test]
+fn test_integer_midpoint_median() {
+    let integer_midpoint_median = IntegerMidpointMedian;
+    assert_eq!(3, integer_midpoint_median.merge(&2, &4));
+    assert_eq!(2, integer_midpoint_median.merge(&2, &3));
+    assert_eq!(-3, integer_midpoint_median.merge(&-3, &-2));
+    assert_eq!(-1, integer_midpoint_median.merge(&i64::MIN, &i64::MAX));
+    assert_eq!(u64::MAX, integer_midpoint_median.merge(&u64::MAX, &u64::MAX));
+
+    let mut heap = MedianHeap::new(IntegerMidpointMedian);
+    heap.push(i32::MAX);
+    heap.push(i32::MAX - 2);
+    assert_eq!(i32::MAX - 1, heap.get_median().unwrap());
+}
+
+#[test]
+fn test_float_midpoint_median() {
+    let float_midpoint_median = FloatMidpointMedian;
+    assert_eq!(2.5, float_midpoint_median.merge(&2.0, &3.0));
+    assert_eq!(-0.25f32, float_midpoint_median.merge(&-1.0, &0.5));
+    assert_eq!(f64::MAX, float_midpoint_median.merge(&f64::MAX, &f64::MAX));
+
+    let mut heap = MedianHeap::new(FloatMidpointMedian);
+    heap.push(AssertOrd(1.0));
+    heap.push(AssertOrd(2.0));
+    assert_eq!(1.5, heap.get_median().unwrap().into_inner());
 }
 
 #[test]
 fn test_median_heap() {
-    let mut heap = MedianHeap::new(MidpointMedian);
+    let mut heap = MedianHeap::new(IntegerMidpointMedian);
     heap.push(1);
     heap.push(2);
     heap.push(3);
@@ -37,7 +96,7 @@ fn test_median_heap() {
 
 #[test]
 fn test_median_heap_clear() {
-    let mut heap = MedianHeap::new(MidpointMedian);
+    let mut heap = MedianHeap::new(IntegerMidpointMedian);
     heap.push(1);
     heap.push(2);
     heap.push(3);
@@ -55,15 +114,15 @@ fn test_median_heap_clear() {
 
 #[test]
 fn test_median_heap_is_empty() {
-    let mut heap = MedianHeap::new(MidpointMedian);
-    assert_eq!(true, heap.is_empty());
+    let mut heap = MedianHeap::new(IntegerMidpointMedian);
+    assert!(heap.is_empty());
     heap.push(1);
-    assert_eq!(false, heap.is_empty());
+    assert!(!heap.is_empty());
 }
 
 #[test]
 fn test_median_heap_get_median() {
-    let mut heap = MedianHeap::new(MidpointMedian);
+    let mut heap = MedianHeap::new(IntegerMidpointMedian);
     heap.push(1);
     heap.push(2);
     heap.push(3);
@@ -73,7 +132,7 @@ fn test_median_heap_get_median() {
 
 #[test]
 fn test_median_heap_push() {
-    let mut heap = MedianHeap::new(MidpointMedian);
+    let mut heap = MedianHeap::new(IntegerMidpointMedian);
     heap.push(1);
     heap.push(2);
     heap.push(3);
@@ -91,7 +150,7 @@ fn test_median_heap_push() {
 
 #[test]
 fn test_median_heap_pop() {
-    let mut heap = MedianHeap::new(MidpointMedian);
+    let mut heap = MedianHeap::new(IntegerMidpointMedian);
     heap.push(1);
     heap.push(2);
     heap.push(3);
@@ -124,8 +183,9 @@ fn test_median_heap_pop() {
 }
 
 #[test]
+#[allow(deprecated)]
 fn test_median_heap_has() {
-  let mut heap = MedianHeap::new(MidpointMedian);
+  let mut heap = MedianHeap::new(IntegerMidpointMedian);
   heap.push(1);
   heap.push(2);
   heap.push(3);
@@ -136,20 +196,20 @@ fn test_median_heap_has() {
   heap.push(7);
   heap.push(7);
 
-  assert_eq!(true, heap.has(&1));
-  assert_eq!(true, heap.has(&2));
-  assert_eq!(true, heap.has(&3));
-  assert_eq!(true, heap.has(&4));
-  assert_eq!(true, heap.has(&5));
-  assert_eq!(true, heap.has(&6));
-  assert_eq!(true, heap.has(&7));
-  assert_eq!(false, heap.has(&8));
-  assert_eq!(false, heap.has(&9));
+  assert!(heap.has(&1));
+  assert!(heap.has(&2));
+  assert!(heap.has(&3));
+  assert!(heap.has(&4));
+  assert!(heap.has(&5));
+  assert!(heap.has(&6));
+  assert!(heap.has(&7));
+  assert!(!heap.has(&8));
+  assert!(!heap.has(&9));
 }
 
 #[test]
 fn test_median_heap_delete() {
-  let mut heap = MedianHeap::new(MidpointMedian);
+  let mut heap = MedianHeap::new(IntegerMidpointMedian);
   heap.push(1);
   heap.push(2);
   heap.push(3);
@@ -174,7 +234,7 @@ fn test_median_heap_delete() {
 #[test]
 fn test_median_heap_fromiter() {
   let iter = vec![1, 2, 3, 4, 5, 6, 7, 7, 7].into_iter();
-  let heap: MedianHeap<i32, MidpointMedian> = MedianHeap::from_iter(iter);
+  let heap: MedianHeap<i32, IntegerMidpointMedian> = MedianHeap::from_iter(iter);
 
   assert_eq!(5, heap.get_median().unwrap());
   assert_eq!(9, heap.len());
@@ -182,7 +242,7 @@ fn test_median_heap_fromiter() {
 
 #[test]
 fn test_median_heap_clone() {
-  let mut heap = MedianHeap::new(MidpointMedian);
+  let mut heap = MedianHeap::new(IntegerMidpointMedian);
   heap.push(1);
   heap.push(2);
   heap.push(3);
@@ -196,4 +256,2535 @@ fn test_median_heap_clone() {
   let cloned_heap = heap.clone();
   assert_eq!(5, cloned_heap.get_median().unwrap());
   assert_eq!(9, cloned_heap.len());
-}
\ No newline at end of file
+}
+#[test]
+fn test_median_heap_running_medians() {
+  let medians: Vec<i32> = MedianHeap::running_medians(IntegerMidpointMedian, vec![1, 2, 3, 4, 5]).collect();
+  assert_eq!(vec![1, 1, 2, 2, 3], medians);
+
+  let first: Vec<i32> = MedianHeap::running_medians(LeftHandedMedian, (1..).step_by(2)).take(3).collect();
+  assert_eq!(vec![1, 1, 3], first);
+}
+
+#[test]
+fn test_median_heap_halves() {
+  let heap: MedianHeap<i32, IntegerMidpointMedian> = vec![5, 3, 8, 1, 9, 2, 7].into_iter().collect();
+  let (lower, upper) = heap.halves();
+
+  assert_eq!(vec![1, 2, 3, 5], lower);
+  assert_eq!(vec![7, 8, 9], upper);
+}
+
+#[test]
+fn test_approx_median_heap() {
+  let mut heap = ApproxMedianHeap::new(0.01);
+  assert_eq!(None, heap.get_median());
+
+  // Push a permutation of 0..100_000 so the values don't arrive in order.
+  let n = 100_000u64;
+  for i in 0..n {
+    heap.push(i * 7_919 % n);
+  }
+
+  assert_eq!(n as usize, heap.len());
+  let median = heap.get_median().unwrap();
+  let tolerance = (0.01 * n as f64) as u64;
+  assert!(median.abs_diff(n / 2) <= tolerance);
+  assert!(heap.summary.len() < 1_000);
+
+  // Alternating between both ends pushes every value next to an extreme, so every compress merges tuples at both ends of the summary.
+  let mut heap = ApproxMedianHeap::new(0.01);
+  for i in 0..n as i64 {
+    heap.push(if i % 2 == 0 { i } else { -i });
+  }
+  let median = heap.get_median().unwrap();
+  assert!(median.unsigned_abs() <= 2 * tolerance);
+  assert!(heap.summary.windows(2).all(|pair| pair[0].0 <= pair[1].0));
+  assert_eq!(n as usize, heap.summary.iter().map(|&(_, g, _)| g).sum::<usize>());
+  assert_eq!((-(n as i64 - 1), n as i64 - 2), (heap.summary[0].0, heap.summary[heap.summary.len() - 1].0));
+}
+
+#[test]
+fn test_median_heap_without_add() {
+  let mut heap = MedianHeap::new(LeftHandedMedian);
+  heap.push('c');
+  heap.push('a');
+  heap.push('d');
+  heap.push('b');
+
+  assert_eq!('b', heap.get_median().unwrap());
+  assert_eq!('b', heap.pop().unwrap());
+}
+
+#[test]
+fn test_median_heap_percentile_rank() {
+  let mut heap = MedianHeap::new(IntegerMidpointMedian);
+  assert_eq!(None, heap.percentile_rank(&1));
+
+  for value in [1, 2, 2, 3, 4, 5, 6, 7, 8, 9] {
+    heap.push(value);
+  }
+
+  assert_eq!(Some(0.0), heap.percentile_rank(&0));
+  assert_eq!(Some(0.3), heap.percentile_rank(&2));
+  assert_eq!(Some(0.5), heap.percentile_rank(&4));
+  assert_eq!(Some(1.0), heap.percentile_rank(&9));
+}
+
+#[test]
+fn test_median_heap_pop_min_max() {
+  let mut heap: MedianHeap<i32, IntegerMidpointMedian> = vec![5, 3, 8, 1, 9, 2, 7].into_iter().collect();
+
+  assert_eq!(Some(&1), heap.min());
+  assert_eq!(Some(&9), heap.max());
+
+  assert_eq!(Some(1), heap.pop_min());
+  assert_eq!(Some(9), heap.pop_max());
+  assert_eq!(5, heap.len());
+  assert_eq!(5, heap.get_median().unwrap());
+
+  assert_eq!(Some(2), heap.pop_min());
+  assert_eq!(Some(3), heap.pop_min());
+  assert_eq!(7, heap.get_median().unwrap());
+
+  assert_eq!(Some(8), heap.pop_max());
+  assert_eq!(Some(7), heap.pop_max());
+  assert_eq!(Some(5), heap.pop_min());
+  assert_eq!(None, heap.pop_min());
+  assert_eq!(None, heap.pop_max());
+}
+
+#[test]
+fn test_median_heap_may_contain() {
+  let heap: MedianHeap<i32, IntegerMidpointMedian> = vec![1, 3, 5, 7, 9, 11].into_iter().collect();
+
+  assert!(heap.may_contain(&5, &5));
+  assert!(heap.may_contain(&0, &1));
+  assert!(heap.may_contain(&10, &12));
+  assert!(!heap.may_contain(&-5, &0));
+  assert!(!heap.may_contain(&12, &20));
+  assert!(!heap.may_contain(&2, &2));
+  assert!(!heap.may_contain(&10, &10));
+  assert!(!heap.may_contain(&7, &5));
+
+  let empty: MedianHeap<i32, IntegerMidpointMedian> = MedianHeap::new(IntegerMidpointMedian);
+  assert!(!empty.may_contain(&0, &10));
+}
+
+#[test]
+fn test_median_heap_clone_from() {
+  let source: MedianHeap<i32, IntegerMidpointMedian> = vec![1, 2, 3, 4, 5, 6, 7].into_iter().collect();
+  let mut target: MedianHeap<i32, IntegerMidpointMedian> = vec![10, 20, 30].into_iter().collect();
+
+  target.clone_from(&source);
+
+  assert_eq!(7, target.len());
+  assert_eq!(4, target.get_median().unwrap());
+  assert_eq!(source.halves(), target.halves());
+}
+
+#[test]
+fn test_median_heap_try_delete() {
+  let mut heap = MedianHeap::new(IntegerMidpointMedian);
+  assert_eq!(Err(MedianHeapError::Empty), heap.try_delete(&1));
+
+  for value in [1, 2, 3, 4, 5, 6, 7, 7, 7] {
+    heap.push(value);
+  }
+
+  assert_eq!(Ok(7), heap.try_delete(&7));
+  assert_eq!(8, heap.len());
+  assert!(heap.contains(&7));
+
+  assert_eq!(Ok(1), heap.try_delete(&1));
+  assert_eq!(Ok(2), heap.try_delete(&2));
+  assert_eq!(6, heap.len());
+  assert_eq!(5, heap.get_median().unwrap());
+
+  assert_eq!(Err(MedianHeapError::NotFound), heap.try_delete(&1));
+  assert_eq!(6, heap.len());
+}
+
+#[test]
+fn test_median_heap_push_all() {
+  let values: Vec<i32> = (0..200).map(|i| i * 37 % 101 - 50).collect();
+
+  let mut sequential = MedianHeap::new(IntegerMidpointMedian);
+  let mut batched = MedianHeap::new(IntegerMidpointMedian);
+  for &value in &values[..50] {
+    sequential.push(value);
+    batched.push(value);
+  }
+
+  for &value in &values[50..] {
+    sequential.push(value);
+  }
+  batched.push_all(values[50..].iter().copied());
+
+  let (lower, upper) = sequential.halves();
+  let mut expected = [lower, upper].concat();
+  expected.sort();
+  let (lower, upper) = batched.halves();
+  let mut actual = [lower, upper].concat();
+  actual.sort();
+
+  assert_eq!(sequential.len(), batched.len());
+  assert_eq!(sequential.get_median(), batched.get_median());
+  assert_eq!(expected, actual);
+
+  let mut empty = MedianHeap::new(IntegerMidpointMedian);
+  empty.push_all(vec![3, 1, 2]);
+  assert_eq!(2, empty.get_median().unwrap());
+}
+
+#[test]
+fn test_median_heap_set_median_kind() {
+  struct Offset(i32);
+  impl MergeMedian<i32> for Offset {
+    fn merge(&self, a: &i32, b: &i32) -> i32 {
+      (a + b) / 2 + self.0
+    }
+  }
+
+  let mut heap = MedianHeap::new(Offset(0));
+  heap.push(2);
+  heap.push(4);
+  assert_eq!(0, heap.median_kind().0);
+  assert_eq!(3, heap.get_median().unwrap());
+
+  heap.set_median_kind(Offset(10));
+  assert_eq!(10, heap.median_kind().0);
+  assert_eq!(13, heap.get_median().unwrap());
+  assert_eq!(2, heap.len());
+}
+
+#[test]
+fn test_median_heap_median_f64() {
+  let mut heap = MedianHeap::new(IntegerMidpointMedian);
+  assert_eq!(None, heap.median_f64());
+
+  heap.push(2u8);
+  assert_eq!(Some(2.0), heap.median_f64());
+
+  heap.push(3);
+  assert_eq!(2, heap.get_median().unwrap());
+  assert_eq!(Some(2.5), heap.median_f64());
+
+  heap.push(10);
+  assert_eq!(Some(3.0), heap.median_f64());
+}
+
+fn sorted_median<K: MergeMedian<i32>>(kind: &K, values: &[i32]) -> Option<i32> {
+  let mut sorted = values.to_vec();
+  sorted.sort();
+  let n = sorted.len();
+  if n == 0 {
+    None
+  } else if n % 2 == 1 {
+    Some(sorted[n / 2])
+  } else {
+    Some(kind.merge(&sorted[n / 2 - 1], &sorted[n / 2]))
+  }
+}
+
+proptest::proptest! {
+  #[test]
+  fn prop_median_heap_matches_sorted_median(values in proptest::collection::vec(-1000..1000i32, 0..200)) {
+    let mut heap = MedianHeap::new(IntegerMidpointMedian);
+    for (i, &value) in values.iter().enumerate() {
+      heap.push(value);
+      proptest::prop_assert_eq!(sorted_median(&IntegerMidpointMedian, &values[..=i]), heap.get_median());
+    }
+  }
+
+  #[test]
+  fn prop_median_heap_matches_sorted_median_with_duplicates(values in proptest::collection::vec(0..4i32, 0..200)) {
+    let mut heap = MedianHeap::new(LeftHandedMedian);
+    for (i, &value) in values.iter().enumerate() {
+      heap.push(value);
+      proptest::prop_assert_eq!(sorted_median(&LeftHandedMedian, &values[..=i]), heap.get_median());
+    }
+  }
+
+  #[test]
+  fn prop_median_heap_delete_matches_sorted_median(ops in proptest::collection::vec((proptest::bool::ANY, 0..6i32), 0..200)) {
+    let mut heap = MedianHeap::new(IntegerMidpointMedian);
+    let mut reference = Vec::new();
+    for (is_delete, value) in ops {
+      if is_delete {
+        heap.delete(&value);
+        reference.retain(|x| *x != value);
+      } else {
+        heap.push(value);
+        reference.push(value);
+      }
+      proptest::prop_assert_eq!(reference.len(), heap.len());
+      proptest::prop_assert_eq!(sorted_median(&IntegerMidpointMedian, &reference), heap.get_median());
+      proptest::prop_assert_eq!(reference.iter().min(), heap.min());
+      proptest::prop_assert_eq!(reference.iter().max(), heap.max());
+    }
+  }
+
+  #[test]
+  fn prop_median_heap_with_slack_matches_sorted_median(slack in 1..6usize, ops in proptest::collection::vec((0..3u8, -50..50i32), 0..200)) {
+    let mut heap = MedianHeap::with_slack(IntegerMidpointMedian, slack);
+    let mut reference = Vec::new();
+    for (op, value) in ops {
+      match op {
+        0 => {
+          heap.delete(&value);
+          reference.retain(|x| *x != value);
+        }
+        1 if !reference.is_empty() => {
+          reference.sort();
+          let n = reference.len();
+          let median = if n % 2 == 1 {
+            reference.remove(n / 2)
+          } else {
+            let right = reference.remove(n / 2);
+            let left = reference.remove(n / 2 - 1);
+            IntegerMidpointMedian.merge(&left, &right)
+          };
+          proptest::prop_assert_eq!(Some(median), heap.pop());
+        }
+        _ => {
+          heap.push(value);
+          reference.push(value);
+        }
+      }
+      proptest::prop_assert!(heap.is_balanced());
+      proptest::prop_assert_eq!(sorted_median(&IntegerMidpointMedian, &reference), heap.get_median());
+    }
+  }
+}
+
+#[test]
+fn test_median_heap_delete_merged_median() {
+  let mut heap = MedianHeap::new(IntegerMidpointMedian);
+  heap.push(1);
+  heap.push(3);
+
+  // The merged median 2 isn't stored in the heap, so nothing should be removed.
+  heap.delete(&2);
+  assert_eq!(2, heap.len());
+  assert_eq!(2, heap.get_median().unwrap());
+
+  heap.push(3);
+  heap.push(3);
+  heap.delete(&3);
+  assert_eq!(1, heap.len());
+  assert_eq!(1, heap.get_median().unwrap());
+}
+
+#[test]
+fn test_median_heap_replace_median() {
+  let mut heap = MedianHeap::new(IntegerMidpointMedian);
+  assert_eq!(None, heap.replace_median(5));
+  assert_eq!(1, heap.len());
+
+  for value in [1, 9, 3, 7] {
+    heap.push(value);
+  }
+
+  assert_eq!(Some(5), heap.replace_median(4));
+  assert_eq!(5, heap.len());
+  assert_eq!(4, heap.get_median().unwrap());
+
+  assert_eq!(Some(4), heap.replace_median(8));
+  assert_eq!(5, heap.len());
+  assert_eq!(7, heap.get_median().unwrap());
+
+  heap.push(10);
+  assert_eq!(Some(7), heap.replace_median(0));
+  assert_eq!(5, heap.len());
+  assert_eq!(3, heap.get_median().unwrap());
+}
+
+#[test]
+fn test_median_heap_balance() {
+  let mut heap = MedianHeap::new(IntegerMidpointMedian);
+  assert_eq!(0, heap.balance());
+  assert_eq!((0, 0), heap.halves_len());
+
+  for value in 0..10 {
+    heap.push(value);
+    assert!((0..=1).contains(&heap.balance()));
+  }
+  assert_eq!((5, 5), heap.halves_len());
+
+  heap.delete(&0);
+  heap.delete(&1);
+  heap.delete(&2);
+  assert_eq!(1, heap.balance());
+  assert_eq!((4, 3), heap.halves_len());
+}
+
+#[test]
+fn test_median_heap_len_lower_upper() {
+  let mut heap = MedianHeap::new(LeftHandedMedian);
+  assert_eq!((0, 0), (heap.len_lower(), heap.len_upper()));
+
+  for value in [7, 3, 9, 1, 5, 5, 8, 2] {
+    heap.push(value);
+    let (lower, upper) = (heap.len_lower(), heap.len_upper());
+    assert_eq!(heap.halves_len(), (lower, upper));
+    assert_eq!(heap.len().div_ceil(2), lower);
+
+    // The root of each half sits right at the split in ascending order.
+    assert_eq!(heap.get_sorted(lower - 1), heap.peak_max());
+    assert_eq!(heap.get_sorted(lower), heap.peak_min());
+    let n = heap.len();
+    assert_eq!(heap.median_candidate_refs(), heap.get_sorted((n - 1) / 2).zip(heap.get_sorted(n / 2)));
+  }
+}
+
+#[test]
+fn test_median_heap_get_median_or() {
+  let mut heap = MedianHeap::new(IntegerMidpointMedian);
+  assert_eq!(-1, heap.get_median_or(-1));
+  assert_eq!(0, heap.get_median_or_default());
+
+  heap.push(4);
+  heap.push(6);
+  assert_eq!(5, heap.get_median_or(-1));
+  assert_eq!(5, heap.get_median_or_default());
+}
+
+#[test]
+fn test_time_weighted_median_heap() {
+  let mut heap = TimeWeightedMedianHeap::new(IntegerMidpointMedian);
+  assert_eq!(None, heap.get_median());
+
+  heap.push_at(5, 100);
+  assert_eq!(5, heap.get_median().unwrap());
+
+  // Values with the same timestamp replace each other without gaining weight.
+  heap.push_at(1, 100);
+  heap.push_at(9, 110);
+  heap.push_at(3, 111);
+  assert_eq!(4, heap.len());
+  assert_eq!(11, heap.total_duration());
+  assert_eq!(1, heap.get_median().unwrap());
+
+  // 1 and 3 were both current for 10 time units, 9 for 1.
+  heap.push_at(2, 121);
+  assert_eq!(21, heap.total_duration());
+  assert_eq!(3, heap.get_median().unwrap());
+
+  // 1 and 2 now cover exactly half of the elapsed time, so 2 and 3 are merged.
+  heap.push_at(7, 122);
+  assert_eq!(22, heap.total_duration());
+  assert_eq!(2, heap.get_median().unwrap());
+
+  // A timestamp in the past doesn't add any weight.
+  heap.push_at(100, 50);
+  assert_eq!(22, heap.total_duration());
+}
+
+#[test]
+fn test_median_heap_quartiles() {
+  let mut heap = MedianHeap::new(IntegerMidpointMedian);
+  assert_eq!(None, heap.q1());
+  assert_eq!(None, heap.q3());
+  assert_eq!(None, heap.iqr());
+
+  heap.push(5);
+  assert_eq!(Some(5), heap.q1());
+  assert_eq!(Some(5), heap.q3());
+  assert_eq!(Some(0), heap.iqr());
+
+  for value in [30, 10, 20, 50, 40, 60, 80, 70] {
+    heap.push(value);
+  }
+
+  // Sorted: 5, 10, 20, 30, 40, 50, 60, 70, 80
+  assert_eq!(Some(20), heap.q1());
+  assert_eq!(Some(60), heap.q3());
+  assert_eq!(Some(40), heap.iqr());
+}
+
+#[test]
+fn test_median_heap_push_ref() {
+  let values = [4, 8, 6];
+  let mut heap = MedianHeap::new(IntegerMidpointMedian);
+  values.iter().for_each(|value| heap.push_ref(value));
+
+  assert_eq!(3, heap.len());
+  assert_eq!(6, heap.get_median().unwrap());
+}
+
+#[test]
+fn test_median_heap_push_cow() {
+  use std::borrow::Cow;
+
+  let borrowed = [4, 8];
+  let mut heap = MedianHeap::new(IntegerMidpointMedian);
+  borrowed.iter().for_each(|value| heap.push_cow(Cow::Borrowed(value)));
+  heap.push_cow(Cow::Owned(6));
+
+  assert_eq!(vec![4, 6, 8], heap.to_sorted_vec());
+  assert_eq!(6, heap.get_median().unwrap());
+}
+
+#[test]
+fn test_median_heap_sorted() {
+  let heap: MedianHeap<i32, IntegerMidpointMedian> = vec![5, 3, 8, 1, 9, 2, 7, 3].into_iter().collect();
+
+  let sorted: Vec<i32> = heap.sorted().copied().collect();
+  assert_eq!(vec![1, 2, 3, 3, 5, 7, 8, 9], sorted);
+  assert_eq!(8, heap.len());
+}
+
+#[test]
+fn test_median_heap_remove_outliers() {
+  let mut heap = MedianHeap::new(IntegerMidpointMedian);
+  heap.remove_outliers(1.5);
+  assert!(heap.is_empty());
+
+  for value in 10..30 {
+    heap.push(value);
+  }
+  heap.push(-500);
+  heap.push(1_000);
+  heap.push(2_000);
+
+  heap.remove_outliers(1.5);
+
+  assert_eq!(20, heap.len());
+  assert_eq!(Some(&10), heap.min());
+  assert_eq!(Some(&29), heap.max());
+  assert!((0..=1).contains(&heap.balance()));
+  assert_eq!(19, heap.get_median().unwrap());
+}
+
+#[test]
+fn test_median_heap_evict_oldest() {
+  let values: Vec<i32> = (0..500).map(|i| i * 31 % 17).collect();
+  let mut heap = MedianHeap::with_policy(IntegerMidpointMedian, 5, EvictionPolicy::EvictOldest);
+
+  for (i, &value) in values.iter().enumerate() {
+    heap.push(value);
+    let window = &values[i.saturating_sub(4)..=i];
+    assert_eq!(window.len(), heap.len());
+    assert_eq!(sorted_median(&IntegerMidpointMedian, window), heap.get_median());
+  }
+}
+
+#[test]
+fn test_median_heap_evict_oldest_stress() {
+  use std::collections::VecDeque;
+
+  // A deterministic pseudo-random sequence, so a failure can be reproduced.
+  let mut state: u64 = 0x9E37_79B9_7F4A_7C15;
+  let mut next = move |bound: u64| {
+    state ^= state << 13;
+    state ^= state >> 7;
+    state ^= state << 17;
+    state % bound
+  };
+
+  for (capacity, range) in [(1, 10), (7, 5), (64, 1000), (100, 20)] {
+    let mut heap = MedianHeap::with_policy(LeftHandedMedian, capacity, EvictionPolicy::EvictOldest);
+    let mut window: VecDeque<i32> = VecDeque::new();
+    for _ in 0..10_000 {
+      match next(20) {
+        // Removing a copy by other means makes the oldest equal entry stale, as if the oldest copy was removed.
+        0 if !window.is_empty() => {
+          let mut sorted: Vec<i32> = window.iter().copied().collect();
+          sorted.sort();
+          let median = sorted[(sorted.len() - 1) / 2];
+          let pair = sorted.len().is_multiple_of(2).then(|| sorted[sorted.len() / 2]);
+          heap.pop();
+          for value in std::iter::once(median).chain(pair) {
+            let index = window.iter().position(|x| *x == value).unwrap();
+            window.remove(index);
+          }
+        }
+        1 => {
+          let value = next(range) as i32;
+          heap.delete(&value);
+          window.retain(|x| *x != value);
+        }
+        _ => {
+          let value = next(range) as i32;
+          heap.push(value);
+          window.push_back(value);
+          if window.len() > capacity {
+            window.pop_front();
+          }
+        }
+      }
+
+      let values: Vec<i32> = window.iter().copied().collect();
+      let mut sorted = values.clone();
+      sorted.sort();
+      assert_eq!(values.len(), heap.len());
+      assert_eq!(sorted_median(&LeftHandedMedian, &values), heap.get_median());
+      assert_eq!(values.iter().min(), heap.min());
+      assert_eq!(values.iter().max(), heap.max());
+      assert_eq!(sorted.get(sorted.len() / 3), heap.nth_smallest(sorted.len() / 3));
+      assert_eq!(sorted, heap.to_sorted_vec());
+      assert!(heap.is_balanced());
+    }
+  }
+}
+
+#[test]
+fn test_median_heap_evict_extreme_stress() {
+  // A deterministic pseudo-random sequence, so a failure can be reproduced.
+  let mut state: u64 = 0x2545_F491_4F6C_DD1D;
+  let mut next = move |bound: u64| {
+    state ^= state << 13;
+    state ^= state >> 7;
+    state ^= state << 17;
+    state % bound
+  };
+
+  for (capacity, range) in [(1, 10), (7, 5), (64, 1000), (100, 20)] {
+    let mut heap = MedianHeap::with_policy(LeftHandedMedian, capacity, EvictionPolicy::EvictExtreme);
+    let mut values: Vec<i32> = Vec::new();
+    for _ in 0..10_000 {
+      if next(10) == 0 && !values.is_empty() {
+        assert_eq!(Some(*values.iter().min().unwrap()), heap.pop_min());
+        values.remove(0);
+      } else {
+        let value = next(range) as i32;
+        let above_median = sorted_median(&LeftHandedMedian, &values).is_some_and(|median| value > median);
+        heap.push(value);
+        values.push(value);
+        values.sort();
+        if values.len() > capacity {
+          if above_median {
+            values.pop();
+          } else {
+            values.remove(0);
+          }
+        }
+      }
+
+      assert_eq!(values.len(), heap.len());
+      assert_eq!(sorted_median(&LeftHandedMedian, &values), heap.get_median());
+      assert_eq!(values.first(), heap.min());
+      assert_eq!(values.last(), heap.max());
+      assert_eq!(values, heap.to_sorted_vec());
+      assert!(heap.is_balanced());
+    }
+  }
+}
+
+#[test]
+fn test_median_heap_set_window() {
+  let mut heap = MedianHeap::with_policy(IntegerMidpointMedian, 8, EvictionPolicy::EvictOldest);
+  let mut reference = std::collections::VecDeque::new();
+  let mut window = 8;
+
+  for i in 0..300 {
+    if i % 50 == 49 {
+      // Shrinking drops the oldest values, while growing lets the window fill up again.
+      window = if window == 8 { 3 } else { 8 };
+      heap.set_window(window);
+      while reference.len() > window {
+        reference.pop_front();
+      }
+    }
+
+    let value = i * 37 % 23;
+    heap.push(value);
+    reference.push_back(value);
+    if reference.len() > window {
+      reference.pop_front();
+    }
+
+    let expected: Vec<i32> = reference.iter().copied().collect();
+    assert_eq!(expected.len(), heap.len());
+    assert_eq!(sorted_median(&IntegerMidpointMedian, &expected), heap.get_median());
+    assert!(heap.is_balanced());
+  }
+
+  let mut farthest = MedianHeap::with_policy(LeftHandedMedian, 5, EvictionPolicy::EvictExtreme);
+  farthest.push_all([1, 10, 11, 12, 13]);
+  farthest.set_window(3);
+  assert_eq!(vec![10, 11, 12], farthest.to_sorted_vec());
+}
+
+#[test]
+#[should_panic(expected = "set_window requires a heap created with a capacity")]
+fn test_median_heap_set_window_unbounded() {
+  let mut heap: MedianHeap<i32, LeftHandedMedian> = MedianHeap::new(LeftHandedMedian);
+  heap.set_window(3);
+}
+
+#[test]
+fn test_median_heap_evict_oldest_after_removals() {
+  let mut heap = MedianHeap::with_policy(IntegerMidpointMedian, 3, EvictionPolicy::EvictOldest);
+  for _ in 0..20 {
+    heap.push(1);
+    heap.pop();
+  }
+
+  heap.push(1);
+  heap.push(2);
+  heap.push(3);
+  heap.delete(&1);
+  heap.push(4);
+  heap.push(5);
+
+  // 1 was already deleted, so pushing 5 evicts 2.
+  assert_eq!(3, heap.len());
+  assert_eq!((vec![3, 4], vec![5]), heap.halves());
+}
+
+#[test]
+fn test_median_heap_evict_farthest_from_median() {
+  let mut heap = MedianHeap::with_farthest_eviction(IntegerMidpointMedian, 4);
+  for value in [10, 11, 12, 13] {
+    heap.push(value);
+  }
+
+  heap.push(50);
+  assert_eq!((vec![10, 11], vec![12, 13]), heap.halves());
+
+  heap.push(-100);
+  assert_eq!((vec![10, 11], vec![12, 13]), heap.halves());
+
+  // 10 and 14 are equally far from the median 12, so the minimum goes.
+  heap.push(14);
+  assert_eq!((vec![11, 12], vec![13, 14]), heap.halves());
+}
+
+#[test]
+fn test_median_heap_evict_extreme() {
+  let mut heap = MedianHeap::with_policy(IntegerMidpointMedian, 3, EvictionPolicy::EvictExtreme);
+  for value in [10, 20, 30] {
+    heap.push(value);
+  }
+
+  heap.push(25);
+  assert_eq!((vec![10, 20], vec![25]), heap.halves());
+
+  heap.push(5);
+  assert_eq!((vec![10, 20], vec![25]), heap.halves());
+
+  heap.push(15);
+  assert_eq!((vec![15, 20], vec![25]), heap.halves());
+}
+
+#[test]
+fn test_median_heap_empty() {
+  let mut heap = MedianHeap::<i32, LeftHandedMedian>::empty();
+  assert!(heap.is_empty());
+
+  heap.push(4);
+  heap.push(2);
+  assert_eq!(2, heap.get_median().unwrap());
+}
+
+#[test]
+fn test_median_heap_drain() {
+  let mut heap: MedianHeap<i32, IntegerMidpointMedian> = vec![5, 3, 8, 1, 9].into_iter().collect();
+
+  let mut drained: Vec<i32> = heap.drain().collect();
+  drained.sort();
+  assert_eq!(vec![1, 3, 5, 8, 9], drained);
+  assert!(heap.is_empty());
+  assert_eq!(None, heap.get_median());
+
+  heap.push(4);
+  assert_eq!(4, heap.get_median().unwrap());
+}
+
+#[test]
+fn test_median_heap_push_all_bounded() {
+  let mut heap = MedianHeap::with_policy(IntegerMidpointMedian, 3, EvictionPolicy::EvictOldest);
+  heap.push_all(1..=10);
+
+  assert_eq!(3, heap.len());
+  assert_eq!((vec![8, 9], vec![10]), heap.halves());
+}
+
+#[test]
+fn test_median_heap_checked_mean() {
+  let mut heap = MedianHeap::new(IntegerMidpointMedian);
+  assert_eq!(None, heap.checked_mean());
+
+  for value in [1u8, 2, 3, 250, 255] {
+    heap.push(value);
+  }
+  assert_eq!(Some(102.2), heap.checked_mean());
+
+  let mut large = MedianHeap::new(LeftHandedMedian);
+  large.push(u128::MAX);
+  large.push(u128::MAX);
+  assert_eq!(Some(u128::MAX as f64), large.checked_mean());
+}
+
+#[test]
+fn test_time_window_median_heap() {
+  let mut heap = TimeWindowMedianHeap::new(LeftHandedMedian, 10);
+  assert_eq!(None, heap.get_median());
+
+  for (value, timestamp) in [(5, 0), (1, 3), (9, 6), (7, 10)] {
+    assert!(heap.push_at(value, timestamp));
+  }
+  assert_eq!(4, heap.len());
+  assert_eq!(5, heap.get_median().unwrap());
+
+  // Evicts the values pushed at 0 and 3.
+  assert!(heap.push_at(8, 14));
+  assert_eq!(3, heap.len());
+  assert_eq!(8, heap.get_median().unwrap());
+
+  // A clock regression within the window is inserted without evicting anything.
+  assert!(heap.push_at(2, 5));
+  assert_eq!(4, heap.len());
+  assert_eq!(Some(14), heap.newest_timestamp());
+  assert_eq!(7, heap.get_median().unwrap());
+
+  // A clock regression outside of the window is rejected.
+  assert!(!heap.push_at(100, 2));
+  assert_eq!(4, heap.len());
+
+  // The regressed value is evicted once it falls out of the window.
+  assert!(heap.push_at(3, 16));
+  assert_eq!(4, heap.len());
+  assert!(!heap.heap().contains(&2));
+  assert_eq!(7, heap.get_median().unwrap());
+}
+
+#[test]
+fn test_median_heap_iter_halves() {
+  let heap: MedianHeap<i32, IntegerMidpointMedian> = vec![6, 2, 9, 4, 7, 1].into_iter().collect();
+
+  let mut lower: Vec<i32> = heap.iter_lower().copied().collect();
+  let mut upper: Vec<i32> = heap.iter_upper().copied().collect();
+  lower.sort();
+  upper.sort();
+
+  assert_eq!(vec![1, 2, 4], lower);
+  assert_eq!(vec![6, 7, 9], upper);
+}
+
+#[test]
+fn test_median_heap_tagged_iter() {
+  let heap: MedianHeap<i32, IntegerMidpointMedian> = vec![6, 2, 9, 4, 7, 1, 5].into_iter().collect();
+  let tagged: Vec<(&i32, Half)> = heap.tagged_iter().collect();
+  assert_eq!(heap.len(), tagged.len());
+
+  // The lower half is yielded first and every lower value is at most every upper value.
+  let split = tagged.iter().position(|(_, half)| *half == Half::Upper).unwrap();
+  assert!(tagged[split..].iter().all(|(_, half)| *half == Half::Upper));
+  assert_eq!(heap.len_lower(), split);
+  let lower_max = tagged[..split].iter().map(|(x, _)| **x).max().unwrap();
+  let upper_min = tagged[split..].iter().map(|(x, _)| **x).min().unwrap();
+  assert_eq!((5, 6), (lower_max, upper_min));
+
+  assert_eq!(0, MedianHeap::<i32, IntegerMidpointMedian>::empty().tagged_iter().count());
+}
+
+proptest::proptest! {
+  #[test]
+  fn prop_ordered_median_heap_matches_median_heap(ops in proptest::collection::vec((0..4u8, 0..6i32), 0..200)) {
+    let mut heap = MedianHeap::new(IntegerMidpointMedian);
+    let mut ordered = OrderedMedianHeap::new(IntegerMidpointMedian);
+    for (op, value) in ops {
+      match op {
+        0 => proptest::prop_assert_eq!(heap.try_delete(&value), ordered.try_delete(&value)),
+        1 => {
+          heap.delete(&value);
+          ordered.delete(&value);
+        }
+        2 => proptest::prop_assert_eq!(heap.pop(), ordered.pop()),
+        _ => {
+          heap.push(value);
+          ordered.push(value);
+        }
+      }
+      proptest::prop_assert_eq!(heap.len(), ordered.len());
+      proptest::prop_assert_eq!(heap.get_median(), ordered.get_median());
+      proptest::prop_assert_eq!(heap.sorted().next(), heap.min());
+      proptest::prop_assert_eq!(heap.sorted().last(), heap.max());
+    }
+  }
+
+  #[test]
+  fn prop_ordered_median_heap_window_matches_median_heap(
+    policy in 0..3u8,
+    capacity in 1..6usize,
+    ops in proptest::collection::vec((0..5u8, 0..8i32), 0..200),
+  ) {
+    let (mut heap, mut ordered) = match policy {
+      0 => (
+        MedianHeap::with_policy(IntegerMidpointMedian, capacity, EvictionPolicy::EvictOldest),
+        OrderedMedianHeap::with_policy(IntegerMidpointMedian, capacity, EvictionPolicy::EvictOldest),
+      ),
+      1 => (
+        MedianHeap::with_policy(IntegerMidpointMedian, capacity, EvictionPolicy::EvictExtreme),
+        OrderedMedianHeap::with_policy(IntegerMidpointMedian, capacity, EvictionPolicy::EvictExtreme),
+      ),
+      _ => (
+        MedianHeap::with_farthest_eviction(IntegerMidpointMedian, capacity),
+        OrderedMedianHeap::with_farthest_eviction(IntegerMidpointMedian, capacity),
+      ),
+    };
+    for (op, value) in ops {
+      match op {
+        0 => proptest::prop_assert_eq!(heap.try_delete(&value), ordered.try_delete(&value)),
+        1 => {
+          heap.delete(&value);
+          ordered.delete(&value);
+        }
+        2 => proptest::prop_assert_eq!(heap.replace_median(value), ordered.replace_median(value)),
+        _ => {
+          heap.push(value);
+          ordered.push(value);
+        }
+      }
+      proptest::prop_assert_eq!(heap.sorted().collect::<Vec<_>>(), ordered.iter().collect::<Vec<_>>());
+      proptest::prop_assert_eq!(heap.get_median(), ordered.get_median());
+      proptest::prop_assert_eq!((heap.len_lower(), heap.len_upper()), (ordered.len_lower(), ordered.len_upper()));
+      proptest::prop_assert_eq!((heap.min(), heap.max()), (ordered.min(), ordered.max()));
+    }
+  }
+}
+
+#[test]
+fn test_ordered_median_heap_window() {
+  let mut window = OrderedMedianHeap::with_policy(IntegerMidpointMedian, 3, EvictionPolicy::EvictOldest);
+  assert_eq!((None, None), (window.min(), window.max()));
+
+  window.extend([5, 1, 9]);
+  assert_eq!((Some(&1), Some(&9)), (window.min(), window.max()));
+  assert_eq!((2, 1), (window.len_lower(), window.len_upper()));
+  assert_eq!(vec![&1, &5], window.iter_lower().collect::<Vec<_>>());
+  assert_eq!(vec![&9], window.iter_upper().collect::<Vec<_>>());
+
+  // Deleting 5 leaves a stale history entry, which the next eviction skips, so 1 is evicted as the oldest value.
+  window.delete(&5);
+  window.extend([7, 3]);
+  assert_eq!(vec![&3, &7, &9], window.iter().collect::<Vec<_>>());
+
+  assert_eq!(Some(7), window.replace_median(4));
+  assert_eq!(vec![&3, &4, &9], window.iter().collect::<Vec<_>>());
+
+  window.clear();
+  window.extend([1, 2, 3, 4]);
+  assert_eq!(vec![&2, &3, &4], window.iter().collect::<Vec<_>>());
+}
+
+#[test]
+fn test_median_heap_split_off_above_median() {
+  let values = vec![5, 3, 8, 3, 1, 9, 5, 5, 2, 7];
+  let mut heap: MedianHeap<i32, IntegerMidpointMedian> = values.clone().into_iter().collect();
+  let median = heap.get_median().unwrap();
+  let upper = heap.split_off_above_median();
+
+  assert!(heap.sorted().all(|x| *x <= median));
+  assert!(upper.sorted().all(|x| *x > median));
+
+  let mut union: Vec<i32> = heap.sorted().chain(upper.sorted()).copied().collect();
+  let mut expected = values;
+  union.sort();
+  expected.sort();
+  assert_eq!(expected, union);
+
+  assert_eq!(sorted_median(&IntegerMidpointMedian, &heap.sorted().copied().collect::<Vec<_>>()), heap.get_median());
+  assert_eq!(sorted_median(&IntegerMidpointMedian, &upper.sorted().copied().collect::<Vec<_>>()), upper.get_median());
+
+  let mut empty: MedianHeap<i32, IntegerMidpointMedian> = MedianHeap::new(IntegerMidpointMedian);
+  assert!(empty.split_off_above_median().is_empty());
+}
+
+#[test]
+fn test_median_heap_split_at_value() {
+  for pivot in -1..12 {
+    for slack in [1, 4] {
+      let values = vec![5, 3, 8, 3, 1, 9, 5, 5, 2, 7, 0, 10];
+      let mut heap = MedianHeap::with_slack(IntegerMidpointMedian, slack);
+      values.iter().for_each(|value| heap.push(*value));
+      let upper = heap.split_at_value(&pivot);
+
+      assert!(heap.sorted().all(|x| *x <= pivot));
+      assert!(upper.sorted().all(|x| *x > pivot));
+      assert!(heap.is_balanced() && upper.is_balanced());
+      assert_eq!(Ok(()), heap.validate());
+      assert_eq!(Ok(()), upper.validate());
+
+      let mut union: Vec<i32> = heap.sorted().chain(upper.sorted()).copied().collect();
+      let mut expected = values;
+      union.sort();
+      expected.sort();
+      assert_eq!(expected, union);
+
+      assert_eq!(sorted_median(&IntegerMidpointMedian, &heap.to_sorted_vec()), heap.get_median());
+      assert_eq!(sorted_median(&IntegerMidpointMedian, &upper.to_sorted_vec()), upper.get_median());
+    }
+  }
+}
+
+#[test]
+fn test_median_heap_assert_ord() {
+  let mut heap = MedianHeap::new(FloatMidpointMedian);
+  for value in [0.5, -1.0, 3.25, 2.0] {
+    heap.push(AssertOrd(value));
+  }
+  assert_eq!(1.25, heap.get_median().unwrap().into_inner());
+
+  let mut bounded = MedianHeap::with_farthest_eviction(LeftHandedMedian, 2);
+  bounded.push(AssertOrd(1.0));
+  bounded.push(AssertOrd(2.0));
+  bounded.push(AssertOrd(100.0));
+  assert!(!bounded.contains(&AssertOrd(100.0)));
+
+  assert!(AssertOrd::try_new(f32::NAN).is_none());
+}
+
+#[test]
+#[should_panic(expected = "incomparable")]
+fn test_median_heap_assert_ord_panics_on_nan() {
+  let mut heap = MedianHeap::new(FloatMidpointMedian);
+  heap.push(AssertOrd(1.0));
+  heap.push(AssertOrd(f64::NAN));
+}
+
+#[test]
+fn test_median_heap_shrink_to() {
+  let mut heap: MedianHeap<i32, IntegerMidpointMedian> = (0..1000).collect();
+  heap.pop_max();
+  for value in 0..900 {
+    heap.delete(&value);
+  }
+
+  heap.shrink_to(300);
+  assert!(heap.capacity() >= heap.len());
+  assert!(heap.capacity() >= 300);
+  assert!(heap.capacity() < 1000);
+
+  heap.shrink_to(0);
+  assert!(heap.capacity() >= heap.len());
+
+  heap.shrink_to_fit();
+  assert!(heap.capacity() >= heap.len());
+}
+
+#[test]
+fn test_median_heap_with_auto_capacity() {
+  let config = AutoCapacity { shrink_below: 0.25, shrink_after: 5, min_capacity: 8 };
+  let mut heap = MedianHeap::with_auto_capacity(IntegerMidpointMedian, config);
+  heap.push_all(0..1000);
+  let peak = heap.capacity();
+  assert!(peak >= 1000);
+
+  // A heap that's half full never counts as mostly empty.
+  while heap.len() > 500 {
+    heap.pop();
+  }
+  for value in 0..20 {
+    heap.push(value);
+    heap.pop();
+  }
+  assert_eq!(peak, heap.capacity());
+
+  while heap.len() > 10 {
+    heap.pop();
+  }
+  for value in 0..5 {
+    heap.push(value);
+    heap.pop();
+  }
+  let shrunk = heap.capacity();
+  assert!(shrunk >= heap.len().max(8));
+  assert!(shrunk < 100);
+
+  // Filling the shrunk heap grows it back to its previous length at once.
+  while heap.len() <= shrunk {
+    heap.push(0);
+  }
+  assert!(heap.capacity() >= 1000);
+  assert_eq!(sorted_median(&IntegerMidpointMedian, &heap.to_sorted_vec()), heap.get_median());
+}
+
+#[test]
+fn test_median_heap_clear_and_shrink() {
+  let mut heap: MedianHeap<i32, IntegerMidpointMedian> = (0..1000).collect();
+  let before = heap.capacity();
+  heap.clear_and_shrink();
+
+  assert_eq!(0, heap.len());
+  assert!(heap.capacity() < before);
+  assert_eq!(None, heap.get_median());
+
+  heap.push(4);
+  heap.push(2);
+  assert_eq!(Some(3), heap.get_median());
+}
+
+#[test]
+fn test_median_heap_update() {
+  let mut heap: MedianHeap<i32, IntegerMidpointMedian> = vec![1, 2, 3, 4, 5, 5].into_iter().collect();
+  let mut reference = vec![1, 2, 3, 4, 5, 5];
+
+  for (old, new) in [(5, 0), (1, 9), (3, 3), (0, 6), (2, -4)] {
+    assert!(heap.update(&old, new));
+    let index = reference.iter().position(|x| *x == old).unwrap();
+    reference[index] = new;
+    assert_eq!(sorted_median(&IntegerMidpointMedian, &reference), heap.get_median());
+  }
+
+  assert!(!heap.update(&42, 1));
+  assert_eq!(6, heap.len());
+  assert!(!heap.contains(&1));
+
+  let mut bounded = MedianHeap::with_policy(LeftHandedMedian, 3, EvictionPolicy::EvictOldest);
+  bounded.push(1);
+  bounded.push(2);
+  bounded.push(3);
+  assert!(bounded.update(&1, 10));
+  bounded.push(4);
+  assert_eq!(vec![&3, &4, &10], bounded.sorted().collect::<Vec<_>>());
+}
+
+#[test]
+fn test_float_median_heap() {
+  let mut heap: FloatMedianHeap = vec![3.0, -1.0, 2.0].into_iter().collect();
+  assert_eq!(2.0, heap.get_median().unwrap());
+
+  heap.push(f64::MAX);
+  assert_eq!(2.5, heap.get_median().unwrap());
+
+  heap.push(-f64::NAN);
+  assert_eq!(2.0, heap.get_median().unwrap());
+
+  heap.delete(-f64::NAN);
+  assert!(!heap.contains(-f64::NAN));
+  heap.push(f64::NAN);
+  assert_eq!(3.0, heap.get_median().unwrap());
+
+  let large: FloatMedianHeap = vec![f64::MAX, f64::MAX].into_iter().collect();
+  assert_eq!(f64::MAX, large.get_median().unwrap());
+
+  let mut zeros = FloatMedianHeap::new();
+  zeros.push(0.0);
+  zeros.push(-0.0);
+  zeros.push(0.0);
+  assert!(zeros.get_median().unwrap().is_sign_positive());
+  zeros.delete(0.0);
+  assert!(zeros.get_median().unwrap().is_sign_negative());
+}
+
+#[test]
+fn test_median_heap_count_less_and_greater() {
+  let values = vec![7, 1, 4, 4, 9, 2, 4, 8, 6];
+  let heap: MedianHeap<i32, IntegerMidpointMedian> = values.clone().into_iter().collect();
+
+  for probe in -1..11 {
+    assert_eq!(values.iter().filter(|x| **x < probe).count(), heap.count_less(&probe));
+    assert_eq!(values.iter().filter(|x| **x > probe).count(), heap.count_greater(&probe));
+  }
+
+  let empty: MedianHeap<i32, IntegerMidpointMedian> = MedianHeap::new(IntegerMidpointMedian);
+  assert_eq!(0, empty.count_less(&1));
+  assert_eq!(0, empty.count_greater(&1));
+}
+
+#[test]
+fn test_median_heap_to_sorted_vec() {
+  let values = vec![5, 3, 9, 3, 1, 8, 2];
+  let heap: MedianHeap<i32, IntegerMidpointMedian> = values.clone().into_iter().collect();
+  let mut expected = values;
+  expected.sort();
+
+  assert_eq!(expected, heap.to_sorted_vec());
+  assert_eq!(7, heap.len());
+  assert_eq!(expected, heap.into_sorted_vec());
+}
+
+#[test]
+fn test_median_heap_from_sorted() {
+  for n in 0..10 {
+    let values: Vec<i32> = (0..n).map(|x| x / 2).collect();
+    let mut heap = MedianHeap::from_sorted(IntegerMidpointMedian, values.clone());
+    assert_eq!(values.len(), heap.len());
+    assert_eq!(sorted_median(&IntegerMidpointMedian, &values), heap.get_median());
+    assert!(heap.balance() == 0 || heap.balance() == 1);
+
+    heap.push(3);
+    let mut reference = values;
+    reference.push(3);
+    assert_eq!(sorted_median(&IntegerMidpointMedian, &reference), heap.get_median());
+  }
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "sorted")]
+fn test_median_heap_from_sorted_rejects_unsorted() {
+  let _ = MedianHeap::from_sorted(IntegerMidpointMedian, vec![3, 1, 2]);
+}
+
+#[test]
+fn test_median_heap_from_two() {
+  let sources = [vec![], vec![4], vec![9, 1, 9], vec![7, 3, 5, 5, 0, 2]];
+  for a in &sources {
+    for b in &sources {
+      let heap = MedianHeap::from_two(LeftHandedMedian, a.iter().copied(), b.clone());
+      let concatenated: Vec<i32> = a.iter().chain(b).copied().collect();
+
+      assert_eq!(concatenated.len(), heap.len());
+      assert_eq!(sorted_median(&LeftHandedMedian, &concatenated), heap.get_median());
+      assert_eq!(Ok(()), heap.validate());
+      assert_eq!(concatenated.iter().min(), heap.min());
+      assert_eq!(concatenated.iter().max(), heap.max());
+    }
+  }
+}
+
+#[test]
+fn test_median_heap_cached_extremes() {
+  let mut heap: MedianHeap<i32, IntegerMidpointMedian> = vec![5, 1, 9, 1, 7].into_iter().collect();
+  assert_eq!(Some(&1), heap.min());
+  assert_eq!(Some(&9), heap.max());
+
+  // Deleting a cached extreme recomputes it.
+  heap.delete(&9);
+  assert_eq!(Some(&7), heap.max());
+  heap.delete(&1);
+  assert_eq!(Some(&5), heap.min());
+
+  // Removing a cached extreme through pop_min, pop_max or try_delete forgets it until the next push.
+  heap.push(1);
+  heap.push(1);
+  assert_eq!(Some(1), heap.pop_min());
+  assert_eq!(Some(&1), heap.min());
+  assert_eq!(Ok(1), heap.try_delete(&1));
+  assert_eq!(Some(&5), heap.min());
+  assert_eq!(Some(7), heap.pop_max());
+  assert_eq!(Some(&5), heap.max());
+  heap.push(6);
+  assert_eq!(Some(&5), heap.min());
+  assert_eq!(Some(&6), heap.max());
+
+  assert!(heap.update(&6, 0));
+  assert_eq!(Some(&0), heap.min());
+  assert_eq!(Some(&5), heap.max());
+
+  heap.push(3);
+  assert_eq!(Some(3), heap.replace_median(8));
+  assert_eq!(Some(&0), heap.min());
+  assert_eq!(Some(&8), heap.max());
+  assert_eq!(Some(5), heap.pop());
+  assert_eq!(Some(&0), heap.min());
+  assert_eq!(Some(&8), heap.max());
+  assert_eq!(Some(4), heap.pop());
+  assert_eq!(None, heap.min());
+
+  heap.push(2);
+  heap.clear();
+  assert_eq!(None, heap.min());
+  assert_eq!(None, heap.max());
+
+  heap.extend(vec![4, -3, 12]);
+  assert_eq!(Some(&-3), heap.min());
+  assert_eq!(Some(&12), heap.max());
+}
+
+#[test]
+fn test_median_heap_two_heaps_accessors() {
+  let mut heap: MedianHeap<i32, IntegerMidpointMedian> = vec![4, 8, 1, 6, 3].into_iter().collect();
+
+  assert_eq!(heap.len(), heap.max_heap().len() + heap.min_heap().len());
+  assert_eq!(Some(&4), heap.max_heap().peek());
+  assert_eq!(Some(&std::cmp::Reverse(6)), heap.min_heap().peek());
+  let (lower, upper) = heap.as_two_heaps();
+  assert!(lower.iter().all(|x| upper.iter().all(|y| *x <= y.0)));
+
+  // Evicted values that are still pending removal aren't exposed.
+  let mut window = MedianHeap::with_policy(IntegerMidpointMedian, 8, EvictionPolicy::EvictOldest);
+  window.push_all(0..100);
+  let (len_lower, len_upper) = window.halves_len();
+  assert_eq!(len_lower, window.max_heap().len());
+  assert_eq!(len_upper, window.min_heap().len());
+  assert_eq!(vec![92, 93, 94, 95], window.max_heap().clone().into_sorted_vec());
+  let (lower, upper) = window.as_two_heaps();
+  let mut values: Vec<i32> = lower.iter().copied().chain(upper.iter().map(|x| x.0)).collect();
+  values.sort();
+  assert_eq!((92..100).collect::<Vec<_>>(), values);
+}
+
+#[test]
+fn test_median_heap_mad() {
+  let empty: MedianHeap<u32, IntegerMidpointMedian> = MedianHeap::new(IntegerMidpointMedian);
+  assert_eq!(None, empty.mad());
+
+  // Unsigned values must not underflow when they're below the median.
+  let heap: MedianHeap<u32, IntegerMidpointMedian> = vec![10, 2, 7, 30, 4, 8].into_iter().collect();
+  // The median is 7 and the distances are 3, 5, 0, 23, 3, 1.
+  assert_eq!(Some(3), heap.mad());
+
+  let heap: MedianHeap<i32, LeftHandedMedian> = vec![5, 5, 5, 5].into_iter().collect();
+  assert_eq!(Some(0), heap.mad());
+
+  // The distance from the median 0 to i32::MIN doesn't fit in an i32.
+  let heap: MedianHeap<i32, IntegerMidpointMedian> = vec![i32::MIN, 0, i32::MAX].into_iter().collect();
+  assert_eq!(None, heap.mad());
+
+  let heap: MedianHeap<i32, IntegerMidpointMedian> = vec![i32::MIN + 1, 0, i32::MAX].into_iter().collect();
+  assert_eq!(Some(i32::MAX), heap.mad());
+
+  let heap: MedianHeap<u8, IntegerMidpointMedian> = vec![0, 128, 255].into_iter().collect();
+  assert_eq!(Some(127), heap.mad());
+}
+
+#[test]
+fn test_median_heap_on_median_change() {
+  use std::sync::{Arc, Mutex};
+
+  let changes = Arc::new(Mutex::new(Vec::new()));
+  let mut heap = MedianHeap::new(IntegerMidpointMedian);
+  let observed = Arc::clone(&changes);
+  heap.on_median_change(move |median| observed.lock().unwrap().push(*median));
+
+  heap.push(4);
+  heap.push(4);
+  heap.push(8);
+  heap.push(0);
+  assert_eq!(vec![4], *changes.lock().unwrap());
+
+  heap.delete(&4);
+  assert_eq!(vec![4], *changes.lock().unwrap());
+  heap.delete(&8);
+  assert_eq!(vec![4, 0], *changes.lock().unwrap());
+
+  // Emptying the heap isn't reported.
+  heap.pop();
+  heap.push(3);
+  assert_eq!(vec![4, 0, 3], *changes.lock().unwrap());
+
+  // A clone doesn't keep the callback, while take keeps it on the emptied heap.
+  let mut clone = heap.clone();
+  clone.push(9);
+  let _ = heap.take();
+  heap.push(1);
+  assert_eq!(vec![4, 0, 3, 1], *changes.lock().unwrap());
+
+  // The callback only has to be Send, so it can own a Cell, and the heap stays Send and Sync.
+  fn assert_send_sync<S: Send + Sync>(_: &S) {}
+  let mut heap = MedianHeap::new(IntegerMidpointMedian);
+  let (sender, receiver) = std::sync::mpsc::channel();
+  let calls = std::cell::Cell::new(0);
+  heap.on_median_change(move |median| {
+    calls.set(calls.get() + 1);
+    sender.send((calls.get(), *median)).unwrap();
+  });
+  assert_send_sync(&heap);
+  heap.push(2);
+  heap.push(6);
+  assert_eq!(vec![(1, 2), (2, 4)], receiver.try_iter().collect::<Vec<_>>());
+}
+
+#[test]
+fn test_median_heap_on_median_change_every_method() {
+  use std::sync::{Arc, Mutex};
+
+  let changes = Arc::new(Mutex::new(Vec::new()));
+  let mut heap: MedianHeap<i32, IntegerMidpointMedian> = vec![1, 2, 3].into_iter().collect();
+  let observed = Arc::clone(&changes);
+  heap.on_median_change(move |median| observed.lock().unwrap().push(*median));
+
+  // The median of an odd-length heap is replaced in place.
+  assert_eq!(Some(2), heap.replace_median(0));
+  assert_eq!(vec![1], *changes.lock().unwrap());
+  assert_eq!(Some(1), heap.replace_median(1));
+  assert_eq!(vec![1], *changes.lock().unwrap());
+
+  // Batched methods report the final median once.
+  heap.push_all([10, 11, 12, 13]);
+  assert_eq!(vec![1, 10], *changes.lock().unwrap());
+  heap.extend(vec![-1, -2]);
+  assert_eq!(vec![1, 10, 3], *changes.lock().unwrap());
+  changes.lock().unwrap().clear();
+
+  // Methods that don't need a MergeMedian median kind report changes as well.
+  let mut heap: MedianHeap<i32, IntegerMidpointMedian> = vec![1, 2, 3, 4, 5].into_iter().collect();
+  let observed = Arc::clone(&changes);
+  heap.on_median_change(move |median| observed.lock().unwrap().push(*median));
+  heap.pop_min();
+  assert!(changes.lock().unwrap().is_empty());
+  heap.pop_min();
+  assert_eq!(vec![4], *changes.lock().unwrap());
+  heap.pop_max();
+  assert_eq!(vec![4, 3], *changes.lock().unwrap());
+  assert_eq!(Ok(3), heap.try_delete(&3));
+  assert_eq!(vec![4, 3, 4], *changes.lock().unwrap());
+  heap.push_all([0, 8]);
+  assert_eq!(vec![4, 0, 8], heap.pop_median_n(3));
+  assert_eq!(vec![4, 3, 4], *changes.lock().unwrap());
+}
+
+#[test]
+fn test_median_heap_replace_median_notifies_once() {
+  use std::sync::atomic::{AtomicUsize, Ordering};
+  use std::sync::Arc;
+
+  let calls = Arc::new(AtomicUsize::new(0));
+  let mut heap: MedianHeap<i32, IntegerMidpointMedian> = vec![1, 5, 9].into_iter().collect();
+  let counter = Arc::clone(&calls);
+  heap.on_median_change(move |_| {
+    counter.fetch_add(1, Ordering::Relaxed);
+  });
+
+  assert_eq!(Some(5), heap.replace_median(4));
+  assert_eq!(1, calls.load(Ordering::Relaxed));
+  assert_eq!(Some(4), heap.get_median());
+  // The value belongs in the upper half, so the median is popped and the value pushed, which still reports once.
+  assert_eq!(Some(4), heap.replace_median(10));
+  assert_eq!(2, calls.load(Ordering::Relaxed));
+  assert_eq!(Some(9), heap.get_median());
+
+  let mut capped = MedianHeap::with_max_duplicates(IntegerMidpointMedian, 1);
+  capped.push_all([1, 5, 9]);
+  assert_eq!(None, capped.replace_median(9));
+  assert_eq!(vec![1, 5, 9], capped.to_sorted_vec());
+}
+
+#[test]
+fn test_median_heap_median_delta() {
+  use std::cmp::Ordering;
+
+  let mut heap = MedianHeap::new(LeftHandedMedian);
+  // An empty heap has no median to remember.
+  assert_eq!(None, heap.median_delta());
+
+  heap.push(5u32);
+  assert_eq!(None, heap.median_delta());
+  assert_eq!(Some((Ordering::Equal, 0)), heap.median_delta());
+
+  heap.push(9);
+  heap.push(9);
+  assert_eq!(Some((Ordering::Greater, 4)), heap.median_delta());
+
+  heap.delete(&9);
+  heap.delete(&9);
+  heap.delete(&5);
+  // The heap is empty again, so the last observed median 9 is kept.
+  assert_eq!(None, heap.median_delta());
+  // A decreasing median of an unsigned type doesn't underflow.
+  heap.push(6);
+  assert_eq!(Some((Ordering::Less, 3)), heap.median_delta());
+
+  heap.clear();
+  heap.push(1);
+  assert_eq!(None, heap.median_delta());
+
+  heap.drain().for_each(drop);
+  heap.push(2);
+  assert_eq!(None, heap.median_delta());
+}
+
+#[test]
+fn test_median_heap_contains() {
+  let mut heap = MedianHeap::new(IntegerMidpointMedian);
+  assert!(!heap.contains(&1));
+
+  heap.push(1);
+  heap.push(5);
+  // The merged median 3 isn't stored in the heap.
+  assert_eq!(3, heap.get_median().unwrap());
+  assert!(!heap.contains(&3));
+  assert!(heap.contains(&1));
+  assert!(heap.contains(&5));
+
+  heap.push(5);
+  heap.push(5);
+  assert!(heap.contains(&5));
+  assert!(!heap.contains(&0));
+  assert!(!heap.contains(&6));
+}
+
+#[test]
+fn test_median_heap_from_iter_preallocates() {
+  let heap: MedianHeap<i32, IntegerMidpointMedian> = (0..1000).collect();
+  assert_eq!(1000, heap.len());
+  assert!(heap.capacity() >= heap.len());
+  // Both halves are allocated once from the size hint instead of growing by doubling.
+  assert!(heap.capacity() <= 1002);
+
+  let heap: MedianHeap<i32, IntegerMidpointMedian> = (0..1000).filter(|x| x % 2 == 0).collect();
+  assert_eq!(500, heap.len());
+  assert_eq!(Some(499), heap.get_median());
+}
+
+#[test]
+fn test_median_heap_median_with() {
+  let mut heap = MedianHeap::new(IntegerMidpointMedian);
+  assert_eq!(None, heap.median_with(|a, _| *a));
+
+  heap.push(2);
+  heap.push(9);
+  assert_eq!(Some(9), heap.median_with(|a, b| *a.max(b)));
+  assert_eq!(Some(5), heap.get_median());
+
+  // The closure isn't used when the median is a single value.
+  heap.push(4);
+  assert_eq!(Some(4), heap.median_with(|_, _| unreachable!()));
+}
+
+#[test]
+fn test_median_heap_median_with_strategy() {
+  let mut heap = MedianHeap::new(IntegerMidpointMedian);
+  assert_eq!(None, heap.median_with_strategy(|_| unreachable!(), |_, _| unreachable!()));
+
+  heap.push(7);
+  assert_eq!(Some(70), heap.median_with_strategy(|x| x * 10, |_, _| unreachable!()));
+
+  heap.push(1);
+  assert_eq!(Some(1), heap.median_with_strategy(|_| unreachable!(), |a, b| *a.min(b)));
+  assert_eq!(Some(7), heap.median_with_strategy(|_| unreachable!(), |_, b| *b));
+
+  // The even closure gets the smaller candidate first, also on a heap with slack.
+  let mut heap = MedianHeap::with_slack(IntegerMidpointMedian, 3);
+  heap.push_all([5, 6, 7, 8, 1, 2]);
+  assert_eq!(Some(56), heap.median_with_strategy(|_| unreachable!(), |a, b| a * 10 + b));
+  heap.push(3);
+  assert_eq!(Some(5), heap.median_with_strategy(|x| *x, |_, _| unreachable!()));
+}
+
+#[test]
+fn test_median_heap_swap_contents() {
+  let mut left: MedianHeap<i32, LeftHandedMedian> = vec![1, 2, 3, 4].into_iter().collect();
+  let mut right: MedianHeap<i32, LeftHandedMedian> = MedianHeap::new(LeftHandedMedian);
+  right.push(6);
+  right.push(8);
+
+  left.swap_contents(&mut right);
+  assert_eq!(vec![6, 8], left.to_sorted_vec());
+  assert_eq!(vec![1, 2, 3, 4], right.to_sorted_vec());
+  assert_eq!(Some(6), left.get_median());
+  assert_eq!(Some(2), right.get_median());
+  assert_eq!(Some(&1), right.min());
+  assert_eq!(Some(&8), left.max());
+
+  // Each heap keeps its own median kind.
+  let mut midpoint: MedianHeap<i32, IntegerMidpointMedian> = vec![10, 20].into_iter().collect();
+  let mut other: MedianHeap<i32, IntegerMidpointMedian> = vec![1, 3, 5, 7].into_iter().collect();
+  midpoint.swap_contents(&mut other);
+  assert_eq!(Some(4), midpoint.get_median());
+  assert_eq!(Some(15), other.get_median());
+
+  // A heap bounded with EvictOldest keeps evicting the oldest value after receiving values without a known order.
+  let mut bounded = MedianHeap::with_policy(LeftHandedMedian, 3, EvictionPolicy::EvictOldest);
+  bounded.push(100);
+  let mut unbounded: MedianHeap<i32, LeftHandedMedian> = vec![1, 2, 3].into_iter().collect();
+  bounded.swap_contents(&mut unbounded);
+  assert_eq!(vec![100], unbounded.to_sorted_vec());
+  for value in 10..20 {
+    bounded.push(value);
+  }
+  assert_eq!(vec![17, 18, 19], bounded.to_sorted_vec());
+}
+
+#[test]
+fn test_median_heap_histogram() {
+  let empty: MedianHeap<i32, IntegerMidpointMedian> = MedianHeap::new(IntegerMidpointMedian);
+  assert!(empty.histogram(4).is_empty());
+
+  let heap: MedianHeap<i32, IntegerMidpointMedian> = vec![0, 3, 3, 4, 7, 8, 12].into_iter().collect();
+  assert!(heap.histogram(0).is_empty());
+  assert_eq!(vec![(0, 12, 7)], heap.histogram(1));
+  assert_eq!(vec![(0, 4, 3), (4, 8, 2), (8, 12, 2)], heap.histogram(3));
+
+  let histogram = heap.histogram(5);
+  assert_eq!(5, histogram.len());
+  assert_eq!(7, histogram.iter().map(|(_, _, count)| count).sum::<usize>());
+
+  let heap: MedianHeap<u8, IntegerMidpointMedian> = vec![5, 5, 5].into_iter().collect();
+  assert_eq!(vec![(5, 5, 3)], heap.histogram(2));
+
+  let heap: MedianHeap<i32, IntegerMidpointMedian> = vec![0, 1, 2, 2].into_iter().collect();
+  assert_eq!(vec![(0, 1, 1), (1, 2, 3)], heap.histogram(5));
+  assert_eq!(vec![(0, 1, 1), (1, 2, 3)], heap.histogram(2));
+
+  // The extremes lie too far apart to be subtracted in T.
+  let heap: MedianHeap<i8, IntegerMidpointMedian> = vec![-100, 100].into_iter().collect();
+  assert_eq!(vec![(-100, 0, 1), (0, 100, 1)], heap.histogram(2));
+  let heap: MedianHeap<i32, IntegerMidpointMedian> = vec![i32::MIN, 0, i32::MAX].into_iter().collect();
+  assert_eq!(vec![(i32::MIN, 0, 1), (0, i32::MAX, 2)], heap.histogram(2));
+  let heap: MedianHeap<i64, IntegerMidpointMedian> = vec![i64::MIN, -1, 1, i64::MAX].into_iter().collect();
+  let histogram = heap.histogram(4);
+  assert_eq!(4, histogram.len());
+  assert_eq!((i64::MIN, i64::MAX), (histogram[0].0, histogram[3].1));
+  assert_eq!(vec![1, 1, 1, 1], histogram.iter().map(|(_, _, count)| *count).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_median_heap_pop_median_n() {
+  let mut heap: MedianHeap<i32, IntegerMidpointMedian> = vec![10, 20, 30, 40, 50, 60].into_iter().collect();
+  assert_eq!(vec![30, 40, 20], heap.pop_median_n(3));
+  assert_eq!(vec![10, 50, 60], heap.to_sorted_vec());
+  assert_eq!(Some(50), heap.get_median());
+  assert!(heap.balance() == 0 || heap.balance() == 1);
+  assert_eq!(Some(&10), heap.min());
+  assert_eq!(Some(&60), heap.max());
+
+  assert!(heap.pop_median_n(0).is_empty());
+  assert_eq!(vec![50, 10, 60], heap.pop_median_n(10));
+  assert!(heap.is_empty());
+  assert_eq!(None, heap.min());
+}
+
+#[test]
+fn test_median_heap_from_vec_and_slice() {
+  let values = vec![9, 4, 7, 1, 4, 8];
+  let heap: MedianHeap<i32, IntegerMidpointMedian> = values.clone().into();
+  assert_eq!(6, heap.len());
+  assert_eq!(0, heap.balance());
+  assert_eq!(sorted_median(&IntegerMidpointMedian, &values), heap.get_median());
+
+  let heap: MedianHeap<i32, LeftHandedMedian> = MedianHeap::from(&values[1..]);
+  assert_eq!(1, heap.balance());
+  assert_eq!(sorted_median(&LeftHandedMedian, &values[1..]), heap.get_median());
+  assert_eq!(Some(&1), heap.min());
+  assert_eq!(Some(&8), heap.max());
+
+  let empty: MedianHeap<i32, IntegerMidpointMedian> = Vec::new().into();
+  assert!(empty.is_empty());
+}
+
+#[test]
+fn test_median_heap_variance() {
+  fn brute_force(values: &[i32]) -> Option<f64> {
+    if values.is_empty() {
+      return None
+    }
+    let mean = values.iter().map(|&x| x as f64).sum::<f64>() / values.len() as f64;
+    Some(values.iter().map(|&x| (x as f64 - mean).powi(2)).sum::<f64>() / values.len() as f64)
+  }
+  let close = |a: Option<f64>, b: Option<f64>| match (a, b) {
+    (Some(a), Some(b)) => (a - b).abs() < 1e-9,
+    (a, b) => a == b,
+  };
+
+  let mut heap: MedianHeap<i32, IntegerMidpointMedian> = MedianHeap::new(IntegerMidpointMedian);
+  assert_eq!(None, heap.variance());
+  heap.track_variance();
+  assert_eq!(None, heap.variance());
+
+  for value in [4, 8, 15, 16, 23, 42, 8] {
+    heap.push(value);
+  }
+  assert!(close(brute_force(&[4, 8, 15, 16, 23, 42, 8]), heap.variance()));
+
+  assert_eq!(Some(15), heap.pop());
+  assert!(close(brute_force(&[4, 8, 16, 23, 42, 8]), heap.variance()));
+
+  heap.delete(&8);
+  assert!(close(brute_force(&[4, 16, 23, 42]), heap.variance()));
+  assert!(close(brute_force(&[4, 16, 23, 42]).map(f64::sqrt), heap.std_dev()));
+
+  let untracked: MedianHeap<i32, IntegerMidpointMedian> = vec![4, 16, 23, 42].into_iter().collect();
+  assert!(close(untracked.variance(), heap.variance()));
+
+  heap.clear();
+  assert_eq!(None, heap.variance());
+  heap.push(7);
+  assert_eq!(Some(0.0), heap.variance());
+}
+
+#[test]
+fn test_median_of() {
+  let values = [7, 3, 9, 1, 3, 8];
+  assert_eq!(sorted_median(&IntegerMidpointMedian, &values), median_of(IntegerMidpointMedian, &values));
+  assert_eq!(sorted_median(&LeftHandedMedian, &values[1..]), median_of(LeftHandedMedian, &values[1..]));
+  assert_eq!([7, 3, 9, 1, 3, 8], values);
+  assert_eq!(Some(5), median_of(LeftHandedMedian, &[5]));
+  assert_eq!(None, median_of(LeftHandedMedian, &[] as &[i32]));
+}
+
+proptest::proptest! {
+  #[test]
+  fn prop_rolling_median_matches_brute_force(values in proptest::collection::vec(-50..50i32, 0..100), window in 1..10usize) {
+    let expected: Vec<i32> = values.windows(window).map(|w| sorted_median(&IntegerMidpointMedian, w).unwrap()).collect();
+    let medians: Vec<i32> = rolling_median(IntegerMidpointMedian, values, window).collect();
+    proptest::prop_assert_eq!(expected, medians);
+  }
+}
+
+#[test]
+fn test_median_heap_descending() {
+  let values = [5, 1, 9, 3, 7, 3];
+  let mut descending = MedianHeap::new(LeftHandedMedian);
+  let mut ascending = MedianHeap::new(RightHandedMedian);
+  for value in values {
+    descending.push(std::cmp::Reverse(value));
+    ascending.push(value);
+    assert_eq!(ascending.get_median(), descending.get_median().map(|x| x.0));
+  }
+  assert_eq!(Some(5), ascending.get_median());
+
+  assert_eq!(ascending.pop(), descending.pop().map(|x| x.0));
+  assert_eq!(ascending.get_median(), descending.get_median().map(|x| x.0));
+}
+
+#[test]
+fn test_median_heap_push_compares_against_roots() {
+  // The midpoints of these values are rounded towards zero, so they lie between the candidates instead of on one of them.
+  let values = [-3, -2, -2, -7, 5, -1, 0, -2, 4, 4];
+  let mut heap = MedianHeap::new(IntegerMidpointMedian);
+  for (i, &value) in values.iter().enumerate() {
+    heap.push(value);
+    assert_eq!(sorted_median(&IntegerMidpointMedian, &values[..=i]), heap.get_median());
+    assert!(heap.balance() == 0 || heap.balance() == 1);
+    let lower_max = heap.iter_lower().max();
+    let upper_min = heap.iter_upper().min();
+    assert!(lower_max.zip(upper_min).is_none_or(|(a, b)| a <= b));
+  }
+}
+
+#[test]
+fn test_median_heap_parts() {
+  use std::cmp::Reverse;
+  use std::collections::BinaryHeap;
+
+  let heap: MedianHeap<i32, IntegerMidpointMedian> = vec![5, 1, 4, 2, 3].into_iter().collect();
+  let (kind, max_heap, min_heap) = heap.into_parts();
+  assert_eq!(3, max_heap.len());
+  assert_eq!(2, min_heap.len());
+  let mut heap = MedianHeap::try_from_parts(kind, max_heap, min_heap).unwrap();
+  assert_eq!(Some(3), heap.get_median());
+  assert_eq!(Some(&1), heap.min());
+  assert_eq!(Some(&5), heap.max());
+  heap.push(6);
+  assert_eq!(Some(3), heap.get_median());
+
+  // A min heap holding one value more is rebalanced.
+  let heap = MedianHeap::from_parts(LeftHandedMedian, BinaryHeap::from(vec![1]), BinaryHeap::from(vec![Reverse(2), Reverse(3)]));
+  assert_eq!(1, heap.balance());
+  assert_eq!(Some(2), heap.get_median());
+
+  let result = MedianHeap::try_from_parts(LeftHandedMedian, BinaryHeap::from(vec![1]), BinaryHeap::from(vec![Reverse(2), Reverse(3), Reverse(4)]));
+  assert_eq!(Some(FromPartsError::Unbalanced { max_len: 1, min_len: 3 }), result.err());
+  let result = MedianHeap::try_from_parts(LeftHandedMedian, BinaryHeap::from(vec![5]), BinaryHeap::from(vec![Reverse(2)]));
+  assert_eq!(Some(FromPartsError::Misordered { max_root: 5, min_root: 2 }), result.err());
+  let result = MedianHeap::try_from_parts(LeftHandedMedian, BinaryHeap::from(vec![1, 2, 3]), BinaryHeap::<Reverse<i32>>::new());
+  let error = result.err().unwrap();
+  assert_eq!(FromPartsError::Unbalanced { max_len: 3, min_len: 0 }, error);
+  assert_eq!("the max heap holds 3 values and the min heap 0, which differ by more than one", error.to_string());
+  let empty = MedianHeap::try_from_parts(LeftHandedMedian, BinaryHeap::<i32>::new(), BinaryHeap::new()).unwrap();
+  assert!(empty.is_empty());
+}
+
+#[test]
+fn test_median_heap_median_interpolated() {
+  let mut heap = MedianHeap::new(IntegerMidpointMedian);
+  assert_eq!(None, heap.median_interpolated());
+  heap.push(-3);
+  assert_eq!(Some(-3.0), heap.median_interpolated());
+  heap.push(0);
+  assert_eq!(-2, heap.get_median().unwrap());
+  assert_eq!(Some(-1.5), heap.median_interpolated());
+  heap.push(10);
+  assert_eq!(Some(0.0), heap.median_interpolated());
+
+  let large: MedianHeap<u128, LeftHandedMedian> = vec![u128::MAX, u128::MAX].into_iter().collect();
+  assert_eq!(Some(u128::MAX as f64), large.median_interpolated());
+}
+
+#[test]
+fn test_median_heap_delete_range() {
+  let values = [1, 2, 3, 3, 4, 5, 6, 7, 8];
+  let heap: MedianHeap<i32, IntegerMidpointMedian> = values.into_iter().collect();
+
+  let mut lower = heap.clone();
+  assert_eq!(6, lower.delete_range(..=5));
+  assert_eq!(vec![6, 7, 8], lower.to_sorted_vec());
+  assert_eq!(Some(7), lower.get_median());
+  assert_eq!(Some(&6), lower.min());
+
+  let mut upper = heap.clone();
+  assert_eq!(7, upper.delete_range(3..));
+  assert_eq!(vec![1, 2], upper.to_sorted_vec());
+  assert_eq!(Some(1), upper.get_median());
+  assert_eq!(Some(&2), upper.max());
+
+  let mut middle = heap.clone();
+  assert_eq!(3, middle.delete_range(2..4));
+  assert_eq!(vec![1, 4, 5, 6, 7, 8], middle.to_sorted_vec());
+  assert_eq!(sorted_median(&IntegerMidpointMedian, &[1, 4, 5, 6, 7, 8]), middle.get_median());
+  assert!(middle.balance() == 0 || middle.balance() == 1);
+
+  assert_eq!(0, middle.delete_range(20..30));
+  assert_eq!(6, middle.len());
+}
+
+proptest::proptest! {
+  #[test]
+  fn prop_sorted_iter_matches_to_sorted_vec(values in proptest::collection::vec(-20..20i32, 0..100)) {
+    let heap: MedianHeap<i32, IntegerMidpointMedian> = values.into_iter().collect();
+    proptest::prop_assert_eq!(heap.to_sorted_vec(), heap.sorted_iter().collect::<Vec<_>>());
+  }
+}
+
+#[test]
+fn test_median_heap_builder() {
+  let mut unbounded: MedianHeap<i32, IntegerMidpointMedian> = MedianHeap::builder().build();
+  unbounded.push_all(0..100);
+  assert_eq!(100, unbounded.len());
+
+  let mut window = MedianHeapBuilder::new(LeftHandedMedian).window(3).build();
+  let mut expected = MedianHeap::with_policy(LeftHandedMedian, 3, EvictionPolicy::EvictOldest);
+  for value in [9, 1, 8, 2, 7, 3] {
+    window.push(value);
+    expected.push(value);
+    assert_eq!(expected.get_median(), window.get_median());
+  }
+  assert_eq!(3, window.len());
+
+  let mut farthest = MedianHeapBuilder::default()
+    .median_kind(IntegerMidpointMedian)
+    .evict_farthest_from_median()
+    .capacity(3)
+    .build();
+  farthest.push_all([5, 6, 7, 100]);
+  assert_eq!(vec![5, 6, 7], farthest.to_sorted_vec());
+
+  // Values that can't be subtracted only need Ord for every other policy.
+  let mut letters: MedianHeap<char, LeftHandedMedian> = MedianHeap::builder().capacity(2).policy(EvictionPolicy::EvictExtreme).build();
+  letters.push_all(['m', 'a', 'z']);
+  assert_eq!(vec!['a', 'm'], letters.to_sorted_vec());
+  let medians: Vec<char> = rolling_median(LeftHandedMedian, "medians".chars(), 3).collect();
+  assert_eq!(vec!['e', 'e', 'd', 'i', 'n'], medians);
+}
+
+#[test]
+fn test_median_heap_builder_max_duplicates() {
+  let mut heap: MedianHeap<i32, IntegerMidpointMedian> = MedianHeap::builder().window(4).max_duplicates(2).build();
+  heap.push_all([1, 1, 1, 2, 3, 4]);
+  assert_eq!(vec![1, 2, 3, 4], heap.to_sorted_vec());
+  heap.push(1);
+  assert_eq!(vec![1, 2, 3, 4], heap.to_sorted_vec());
+  heap.push(1);
+  assert_eq!(vec![1, 1, 3, 4], heap.to_sorted_vec());
+  heap.push(1);
+  assert_eq!(vec![1, 1, 3, 4], heap.to_sorted_vec());
+}
+
+#[test]
+fn test_median_heap_builder_slack() {
+  let mut heap: MedianHeap<i32, LeftHandedMedian> = MedianHeap::builder().window(6).slack(3).build();
+  heap.push_all([1, 2, 3, 4, 5, 6, 7, 8]);
+  assert!(heap.balance() <= 3);
+  assert_eq!(vec![3, 4, 5, 6, 7, 8], heap.to_sorted_vec());
+  assert_eq!(Some(5), heap.get_median());
+}
+
+#[test]
+fn test_median_heap_builder_auto_capacity() {
+  let config = AutoCapacity { shrink_below: 0.25, shrink_after: 5, min_capacity: 8 };
+  let mut heap: MedianHeap<i32, IntegerMidpointMedian> = MedianHeap::builder().auto_capacity(config).slack(2).build();
+  heap.push_all(0..1000);
+  assert!(heap.capacity() >= 1000);
+
+  heap.delete_range(10..);
+  for value in 0..5 {
+    heap.push(value);
+    heap.pop();
+  }
+  assert!(heap.capacity() < 100);
+  assert_eq!(10, heap.len());
+}
+
+#[test]
+#[should_panic(expected = "shrink_after must be greater than 0")]
+fn test_median_heap_builder_invalid_auto_capacity() {
+  let config = AutoCapacity { shrink_after: 0, ..AutoCapacity::default() };
+  let _: MedianHeap<i32, IntegerMidpointMedian> = MedianHeap::builder().auto_capacity(config).build();
+}
+
+#[test]
+#[should_panic(expected = "slack must be greater than 0")]
+fn test_median_heap_builder_zero_slack() {
+  let _: MedianHeap<i32, LeftHandedMedian> = MedianHeap::builder().slack(0).build();
+}
+
+#[test]
+#[should_panic(expected = "max_duplicates must be greater than 0")]
+fn test_median_heap_builder_zero_max_duplicates() {
+  let _: MedianHeap<i32, IntegerMidpointMedian> = MedianHeap::builder().max_duplicates(0).build();
+}
+
+#[test]
+#[should_panic(expected = "EvictFarthestFromMedian subtracts values")]
+fn test_median_heap_with_policy_farthest_from_median() {
+  let _: MedianHeap<i32, IntegerMidpointMedian> = MedianHeap::with_policy(IntegerMidpointMedian, 3, EvictionPolicy::EvictFarthestFromMedian);
+}
+
+#[test]
+#[should_panic(expected = "capacity must be greater than 0")]
+fn test_median_heap_builder_zero_capacity() {
+  let _: MedianHeap<i32, IntegerMidpointMedian> = MedianHeap::builder().capacity(0).build();
+}
+
+#[test]
+fn test_median_heap_median_is_present() {
+  let mut heap = MedianHeap::new(IntegerMidpointMedian);
+  assert_eq!(None, heap.median_is_present());
+  heap.push(2);
+  assert_eq!(Some(true), heap.median_is_present());
+  heap.push(5);
+  assert_eq!(Some(false), heap.median_is_present());
+  heap.push(3);
+  heap.push(4);
+  assert_eq!(Some(3), heap.get_median());
+  assert_eq!(Some(true), heap.median_is_present());
+
+  struct Smallest;
+  impl MergeMedian<i32> for Smallest {
+    fn merge(&self, _: &i32, _: &i32) -> i32 {
+      1
+    }
+  }
+  let mut heap = MedianHeap::new(Smallest);
+  heap.push_all([1, 5, 6, 9]);
+  assert_eq!(Some(true), heap.median_is_present());
+  heap.delete(&1);
+  heap.push(7);
+  assert_eq!(Some(false), heap.median_is_present());
+}
+
+#[test]
+fn test_median_heap_snapshot() {
+  let mut heap: MedianHeap<i32, IntegerMidpointMedian> = MedianHeap::empty();
+  let empty = heap.snapshot();
+  assert_eq!(MedianSnapshot { median: None, len: 0, min: None, max: None, q1: None, q3: None }, *empty);
+
+  heap.push_all(1..=9);
+  let snapshot = heap.snapshot();
+  heap.push_all([100, 200]);
+
+  assert_eq!(MedianSnapshot { median: Some(5), len: 9, min: Some(1), max: Some(9), q1: Some(3), q3: Some(7) }, *snapshot);
+  assert_eq!(Some(6), MedianSnapshot::from(&heap).median);
+}
+
+#[test]
+fn test_median_heap_max_duplicates() {
+  let mut heap = MedianHeap::with_max_duplicates(IntegerMidpointMedian, 2);
+  for value in [5, 5, 5, 5, 1, 9, 9, 9] {
+    heap.push(value);
+  }
+  assert_eq!(vec![1, 5, 5, 9, 9], heap.to_sorted_vec());
+  assert_eq!(Some(5), heap.get_median());
+
+  // Popping the median frees up room for another copy.
+  assert_eq!(Some(5), heap.pop());
+  heap.push(5);
+  heap.push(5);
+  assert_eq!(vec![1, 5, 5, 9, 9], heap.to_sorted_vec());
+
+  heap.delete(&9);
+  heap.extend([9, 9, 9]);
+  assert_eq!(vec![1, 5, 5, 9, 9], heap.to_sorted_vec());
+
+  assert_eq!(Ok(1), heap.try_delete(&1));
+  assert_eq!(Some(9), heap.pop_max());
+  heap.push_all([1, 1, 1, 9, 9]);
+  assert_eq!(vec![1, 1, 5, 5, 9, 9], heap.to_sorted_vec());
+
+  let upper = heap.split_off_above_median();
+  heap.push_all([9, 9, 9]);
+  assert_eq!(vec![1, 1, 5, 5, 9, 9], heap.to_sorted_vec());
+  assert_eq!(vec![9, 9], upper.to_sorted_vec());
+
+  heap.clear();
+  heap.push_all([7, 7, 7]);
+  assert_eq!(2, heap.len());
+}
+
+#[test]
+fn test_median_heap_cmp_median() {
+  use std::cmp::Ordering;
+
+  let mut first = MedianHeap::new(LeftHandedMedian);
+  let mut second = MedianHeap::new(LeftHandedMedian);
+  assert_eq!(None, first.cmp_median(&second));
+  first.push(5);
+  assert_eq!(None, first.cmp_median(&second));
+  assert_eq!(None, second.cmp_median(&first));
+
+  second.push_all([1, 9, 5]);
+  assert_eq!(Some(Ordering::Equal), first.cmp_median(&second));
+  second.push(9);
+  assert_eq!(Some(Ordering::Equal), first.cmp_median(&second));
+  second.push(9);
+  assert_eq!(Some(Ordering::Less), first.cmp_median(&second));
+  assert_eq!(Some(Ordering::Greater), second.cmp_median(&first));
+}
+
+proptest::proptest! {
+  #[test]
+  fn prop_static_median_heap_matches_rolling_median(values in proptest::collection::vec(-20..20i32, 0..100)) {
+    let mut heap: StaticMedianHeap<i32, IntegerMidpointMedian, 4> = StaticMedianHeap::new(IntegerMidpointMedian);
+    for (i, &value) in values.iter().enumerate() {
+      heap.push(value);
+      let window = &values[i.saturating_sub(3)..=i];
+      proptest::prop_assert_eq!(window.len(), heap.len());
+      proptest::prop_assert_eq!(sorted_median(&IntegerMidpointMedian, window), heap.get_median());
+    }
+  }
+}
+
+#[test]
+fn test_static_median_heap() {
+  let mut heap: StaticMedianHeap<u8, LeftHandedMedian, 2> = StaticMedianHeap::new(LeftHandedMedian);
+  assert!(heap.is_empty());
+  assert_eq!(None, heap.get_median());
+  assert_eq!(2, heap.capacity());
+
+  heap.push(7);
+  heap.push(3);
+  assert_eq!(Some(3), heap.get_median());
+  heap.push(9);
+  assert_eq!(Some(3), heap.get_median());
+  heap.push(9);
+  assert_eq!(Some(9), heap.get_median());
+
+  heap.clear();
+  assert!(heap.is_empty());
+  heap.push(4);
+  assert_eq!(Some(4), heap.get_median());
+}
+
+#[test]
+fn test_median_heap_distinct() {
+  let mut heap: MedianHeap<i32, IntegerMidpointMedian> = MedianHeap::empty();
+  assert!(heap.distinct().is_empty());
+  assert_eq!(0, heap.distinct_count());
+
+  heap.push_all([5, 5, 5, 1, 9, 1, 5]);
+  assert_eq!(vec![1, 5, 9], heap.distinct());
+  assert_eq!(3, heap.distinct_count());
+  assert_eq!(7, heap.len());
+
+  heap.delete(&5);
+  assert_eq!(vec![1, 9], heap.distinct());
+}
+
+proptest::proptest! {
+  #[test]
+  fn prop_nth_smallest_and_largest_match_sorted(values in proptest::collection::vec(-20..20i32, 0..60), n in 0..70usize) {
+    let heap: MedianHeap<i32, LeftHandedMedian> = values.iter().copied().collect();
+    let mut sorted = values.clone();
+    sorted.sort();
+    proptest::prop_assert_eq!(sorted.get(n), heap.nth_smallest(n));
+    proptest::prop_assert_eq!(sorted.iter().rev().nth(n), heap.nth_largest(n));
+  }
+}
+
+#[test]
+fn test_median_heap_with_slack() {
+  let mut heap = MedianHeap::with_slack(LeftHandedMedian, 3);
+  // Increasing values all go to the upper half, which may grow two values past the lower half.
+  for value in [1, 2, 3, 4] {
+    heap.push(value);
+  }
+  assert_eq!(-2, heap.balance());
+  assert!(heap.is_balanced());
+  assert_eq!(Some(2), heap.get_median());
+  assert_eq!(Some((&2, &3)), heap.median_candidate_refs());
+  assert_eq!(Some(2.5), heap.median_f64());
+
+  // Removing the median restores the strict balance.
+  assert_eq!(Some(2), heap.pop());
+  assert_eq!(0, heap.balance());
+  assert_eq!(Ok(()), heap.validate());
+
+  // A slack of 1 keeps the strict invariant.
+  let mut strict = MedianHeap::with_slack(LeftHandedMedian, 1);
+  strict.push_all([1, 2, 3, 4, 5, 6]);
+  assert_eq!(0, strict.balance());
+}
+
+#[test]
+#[should_panic(expected = "slack must be greater than 0")]
+fn test_median_heap_with_zero_slack() {
+  let _: MedianHeap<i32, LeftHandedMedian> = MedianHeap::with_slack(LeftHandedMedian, 0);
+}
+
+#[test]
+fn test_median_heap_validate() {
+  use std::cmp::Reverse;
+  use std::collections::BinaryHeap;
+
+  let mut heap: MedianHeap<i32, IntegerMidpointMedian> = MedianHeap::empty();
+  assert!(heap.is_balanced());
+  assert_eq!(Ok(()), heap.validate());
+  for value in [4, 8, 1, 9, 3] {
+    heap.push(value);
+    assert!(heap.is_balanced());
+    assert_eq!(Ok(()), heap.validate());
+  }
+
+  let unordered = MedianHeap::from_parts(IntegerMidpointMedian, BinaryHeap::from(vec![1, 7]), BinaryHeap::from(vec![Reverse(5)]));
+  assert!(unordered.is_balanced());
+  assert!(unordered.validate().unwrap_err().contains("out of order"));
+}
+
+#[test]
+fn test_median_heap_rebuild() {
+  use std::cmp::Reverse;
+  use std::collections::BinaryHeap;
+
+  // Every value of the lower half is greater than every value of the upper half.
+  let lower = BinaryHeap::from(vec![9, 7, 8, 6]);
+  let upper = BinaryHeap::from(vec![Reverse(1), Reverse(3), Reverse(2)]);
+  let mut heap = MedianHeap::from_parts(IntegerMidpointMedian, lower, upper);
+  assert!(heap.validate().is_err());
+
+  heap.rebuild();
+  assert_eq!(Ok(()), heap.validate());
+  assert_eq!(vec![1, 2, 3, 6, 7, 8, 9], heap.to_sorted_vec());
+  assert_eq!(Some(6), heap.get_median());
+  assert_eq!((Some(&1), Some(&9)), (heap.min(), heap.max()));
+
+  // Rebuilding keeps tracked statistics consistent with the values.
+  heap.track_variance();
+  heap.rebuild();
+  heap.push(6);
+  assert_eq!(Some(6), heap.get_median());
+  let mut fresh: MedianHeap<i32, IntegerMidpointMedian> = heap.to_sorted_vec().into_iter().collect();
+  fresh.track_variance();
+  assert!((heap.variance().unwrap() - fresh.variance().unwrap()).abs() < 1e-9);
+
+  let mut empty: MedianHeap<i32, IntegerMidpointMedian> = MedianHeap::empty();
+  empty.rebuild();
+  assert!(empty.is_empty());
+  assert_eq!(Ok(()), empty.validate());
+}
+
+proptest::proptest! {
+  #[test]
+  fn prop_extend_from_sorted_matches_push_all(values in proptest::collection::vec(-50..50i32, 0..60), batch in proptest::collection::vec(-50..50i32, 0..60)) {
+    let mut batch = batch;
+    batch.sort();
+    let mut heap: MedianHeap<i32, IntegerMidpointMedian> = values.iter().copied().collect();
+    let mut expected = heap.clone();
+    heap.extend_from_sorted(&batch);
+    expected.push_all(batch);
+    proptest::prop_assert_eq!(expected.to_sorted_vec(), heap.to_sorted_vec());
+    proptest::prop_assert_eq!(expected.get_median(), heap.get_median());
+    proptest::prop_assert_eq!(expected.min(), heap.min());
+    proptest::prop_assert_eq!(expected.max(), heap.max());
+    proptest::prop_assert!(heap.validate().is_ok());
+  }
+}
+
+#[test]
+fn test_median_heap_extend_from_sorted_bounded() {
+  let mut heap = MedianHeap::with_policy(IntegerMidpointMedian, 3, EvictionPolicy::EvictOldest);
+  heap.push_all([7, 8, 9]);
+  heap.extend_from_sorted(&[1, 2]);
+  assert_eq!(vec![1, 2, 9], heap.to_sorted_vec());
+}
+
+#[test]
+fn test_median_heap_median_candidate_refs() {
+  let mut heap = MedianHeap::new(IntegerMidpointMedian);
+  assert_eq!(None, heap.median_candidate_refs());
+  heap.push(7);
+  assert_eq!(Some((&7, &7)), heap.median_candidate_refs());
+  heap.push(2);
+  assert_eq!(Some((&2, &7)), heap.median_candidate_refs());
+  assert_eq!(Some(4), heap.get_median());
+  heap.push(9);
+  assert_eq!(Some((&7, &7)), heap.median_candidate_refs());
+  heap.pop();
+  assert_eq!(Some((&2, &9)), heap.median_candidate_refs());
+}
+
+#[test]
+fn test_median_heap_constant_stream() {
+  let mut heap = MedianHeap::new(IntegerMidpointMedian);
+  for i in 1..=1000 {
+    heap.push(7);
+    assert!(heap.is_balanced());
+    assert_eq!((i as usize).div_ceil(2), heap.halves_len().0);
+    assert_eq!(Some(7), heap.get_median());
+  }
+
+  // Values equal to either root are routed to keep the heaps balanced.
+  let mut heap = MedianHeap::new(IntegerMidpointMedian);
+  heap.push_all([1, 5]);
+  for _ in 0..10 {
+    heap.push(5);
+    assert!(heap.is_balanced());
+    heap.push(1);
+    assert!(heap.is_balanced());
+  }
+  assert_eq!(Ok(()), heap.validate());
+  assert_eq!(Some(3), heap.get_median());
+}
+
+#[test]
+fn test_median_heap_take() {
+  let mut heap = MedianHeap::with_max_duplicates(IntegerMidpointMedian, 1);
+  heap.track_variance();
+  heap.push_all([3, 3, 1, 5]);
+
+  let taken = heap.take();
+  assert_eq!(vec![1, 3, 5], taken.to_sorted_vec());
+  assert_eq!(Some(3), taken.get_median());
+  assert_eq!(Some(&5), taken.max());
+  assert!((taken.variance().unwrap() - 8.0 / 3.0).abs() < 1e-9);
+
+  assert!(heap.is_empty());
+  assert_eq!(None, heap.get_median());
+  assert_eq!(None, heap.variance());
+  heap.push_all([2, 2, 4]);
+  assert_eq!(vec![2, 4], heap.to_sorted_vec());
+  assert_eq!(Some(1.0), heap.variance());
+
+  let medians = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+  let seen = medians.clone();
+  heap.on_median_change(move |median| seen.lock().unwrap().push(*median));
+  let mut taken = heap.take();
+  taken.push(7);
+  heap.push(9);
+  assert_eq!(vec![9], *medians.lock().unwrap());
+}
+
+#[test]
+fn test_median_heap_push_from_reader() {
+  let mut heap: MedianHeap<i64, IntegerMidpointMedian> = MedianHeap::empty();
+  let input = "3\n-1\r\n  \n 10\t\n\n7";
+  assert_eq!(4, heap.push_from_reader(std::io::Cursor::new(input)).unwrap());
+  assert_eq!(vec![-1, 3, 7, 10], heap.to_sorted_vec());
+  assert_eq!(0, heap.push_from_reader("".as_bytes()).unwrap());
+
+  let error = heap.push_from_reader("1\n2\n2.5\n4\n".as_bytes()).unwrap_err();
+  assert_eq!(std::io::ErrorKind::InvalidData, error.kind());
+  assert!(error.to_string().starts_with("line 3"));
+  assert_eq!(6, heap.len());
+}
+
+#[test]
+fn test_median_heap_try_from_iter() {
+  let heap = MedianHeap::try_from_iter(LeftHandedMedian, vec![Ok::<i32, &str>(5), Ok(1), Ok(3), Ok(2)]).unwrap();
+  assert_eq!(vec![1, 2, 3, 5], heap.to_sorted_vec());
+  assert_eq!(Some(2), heap.get_median());
+
+  // The iterator isn't consumed past the first error.
+  let mut consumed = 0;
+  let results = [Ok(1), Err("first"), Ok(2), Err("second")].into_iter().inspect(|_| consumed += 1);
+  assert_eq!(Err("first"), MedianHeap::try_from_iter(LeftHandedMedian, results).map(|heap| heap.len()));
+  assert_eq!(2, consumed);
+
+  let empty = MedianHeap::try_from_iter(LeftHandedMedian, std::iter::empty::<Result<i32, ()>>()).unwrap();
+  assert!(empty.is_empty());
+}
+
+#[test]
+fn test_median_heap_get_sorted() {
+  let values = [8, 3, 3, 9, 1, 6];
+  let heap: MedianHeap<i32, IntegerMidpointMedian> = values.into_iter().collect();
+  let sorted = heap.to_sorted_vec();
+  for index in 0..8 {
+    assert_eq!(sorted.get(index), heap.get_sorted(index));
+  }
+  assert_eq!(None, MedianHeap::<i32, IntegerMidpointMedian>::empty().get_sorted(0));
+}
+
+#[test]
+fn test_iterator_collect_median() {
+  struct Offset(i32);
+  impl MergeMedian<i32> for Offset {
+    fn merge(&self, a: &i32, b: &i32) -> i32 {
+      a.max(b) + self.0
+    }
+  }
+
+  let heap = (1..=4).collect_median(Offset(100));
+  assert_eq!(4, heap.len());
+  assert_eq!(Some(103), heap.get_median());
+
+  let heap = [5, 1, 3].iter().copied().collect_median(LeftHandedMedian);
+  assert_eq!(Some(3), heap.get_median());
+  assert!(std::iter::empty::<i32>().collect_median(IntegerMidpointMedian).is_empty());
+}
+
+#[test]
+fn test_median_heap_single_value_after_pops() {
+  // Popping down to a single value, which has to end up as the root of the max heap.
+  let mut heap = MedianHeap::new(IntegerMidpointMedian);
+  heap.push_all([4, 1, 9]);
+  assert_eq!(Some(4), heap.pop());
+  assert_eq!(Some(5), heap.pop());
+  assert!(heap.is_empty());
+
+  heap.push_all([4, 1, 9, 6]);
+  assert_eq!(Some(5), heap.pop());
+  assert_eq!((1, 1), heap.halves_len());
+  assert_eq!(Some(9), heap.pop_max());
+  assert_eq!(1, heap.len());
+  assert_eq!((1, 0), heap.halves_len());
+  assert_eq!(Some(1), heap.get_median());
+  assert_eq!(Some(1), heap.pop());
+  assert_eq!(None, heap.pop());
+  assert_eq!(None, heap.get_median());
+
+  // Removing the lower value of two leaves the upper value, which moves over to the max heap.
+  heap.push_all([2, 8]);
+  assert_eq!(Some(2), heap.pop_min());
+  assert_eq!((1, 0), heap.halves_len());
+  assert_eq!(Some(8), heap.get_median());
+  assert_eq!(Some(8), heap.pop());
+
+  heap.push_all([2, 8]);
+  assert_eq!(Ok(2), heap.try_delete(&2));
+  assert_eq!((1, 0), heap.halves_len());
+  assert_eq!(Some(8), heap.get_median());
+  heap.delete(&8);
+  assert_eq!(None, heap.get_median());
+
+  // A single value on either side of parts ends up as the median.
+  let heap = MedianHeap::from_parts(IntegerMidpointMedian, std::collections::BinaryHeap::new(), std::collections::BinaryHeap::from(vec![std::cmp::Reverse(3)]));
+  assert_eq!((1, 0), heap.halves_len());
+  assert_eq!(Some(3), heap.get_median());
+  let mut heap = MedianHeap::from_parts(IntegerMidpointMedian, std::collections::BinaryHeap::from(vec![3]), std::collections::BinaryHeap::new());
+  assert_eq!(Some(3), heap.get_median());
+  assert_eq!(Some(3), heap.pop());
+  assert!(heap.is_empty());
+}
+
+#[test]
+fn test_median_heap_cmp_by_median() {
+  use std::cmp::Ordering;
+
+  let empty: MedianHeap<i32, IntegerMidpointMedian> = MedianHeap::empty();
+  let small: MedianHeap<i32, IntegerMidpointMedian> = vec![-5].into_iter().collect();
+  let short: MedianHeap<i32, IntegerMidpointMedian> = vec![2, 4].into_iter().collect();
+  let long: MedianHeap<i32, IntegerMidpointMedian> = vec![1, 3, 9].into_iter().collect();
+
+  assert_eq!(Ordering::Equal, empty.cmp_by_median(&MedianHeap::empty()));
+  assert_eq!(Ordering::Less, empty.cmp_by_median(&small));
+  assert_eq!(Ordering::Greater, small.cmp_by_median(&empty));
+  assert_eq!(Ordering::Less, small.cmp_by_median(&short));
+  assert_eq!(Ordering::Less, short.cmp_by_median(&long));
+  assert_eq!(Ordering::Greater, long.cmp_by_median(&short));
+  assert_eq!(Ordering::Equal, long.cmp_by_median(&long.clone()));
+}
+
+proptest::proptest! {
+  #[test]
+  fn prop_decayed_median_heap_without_decay_matches_sorted_median(values in proptest::collection::vec(-100..100i32, 0..100)) {
+    let mut heap = DecayedMedianHeap::new(IntegerMidpointMedian, 1.0);
+    for (i, &value) in values.iter().enumerate() {
+      heap.push(value);
+      proptest::prop_assert_eq!(sorted_median(&IntegerMidpointMedian, &values[..=i]), heap.get_median());
+    }
+  }
+}
+
+#[test]
+fn test_decayed_median_heap() {
+  let mut heap = DecayedMedianHeap::new(LeftHandedMedian, 0.9);
+  assert_eq!(None, heap.get_median());
+  for _ in 0..50 {
+    heap.push(1);
+  }
+  assert_eq!(Some(1), heap.get_median());
+
+  // Half of the weight moves to the new values after about ln(2) / ln(1 / 0.9) = 6.6 pushes.
+  for _ in 0..6 {
+    heap.push(100);
+  }
+  assert_eq!(Some(1), heap.get_median());
+  heap.push(100);
+  assert_eq!(Some(100), heap.get_median());
+
+  // Rescaling keeps the median stable over long streams and drops values that no longer matter.
+  let mut heap = DecayedMedianHeap::new(IntegerMidpointMedian, 0.8);
+  for value in 0..10_000 {
+    heap.push(value % 50);
+  }
+  assert_eq!(10_000, heap.len());
+  // The weights of the most recent values 49, 48, 47, ... are 1, 0.8, 0.64, ..., so the values from 46 up hold more than half of the weight.
+  assert_eq!(Some(46), heap.get_median());
+
+  heap.clear();
+  assert!(heap.is_empty());
+  assert_eq!(None, heap.get_median());
+}
+
+#[test]
+#[should_panic(expected = "lambda must be in (0, 1]")]
+fn test_decayed_median_heap_invalid_lambda() {
+  MedianHeap::<i32, IntegerMidpointMedian>::with_decay(IntegerMidpointMedian, 0.0);
+}
+
+#[test]
+#[should_panic(expected = "lambda must not be subnormal")]
+fn test_decayed_median_heap_subnormal_lambda() {
+  MedianHeap::<i32, IntegerMidpointMedian>::with_decay(IntegerMidpointMedian, f64::MIN_POSITIVE / 4.0);
+}
+
+#[test]
+fn test_decayed_median_heap_tiny_lambda() {
+  let mut heap = MedianHeap::with_decay(IntegerMidpointMedian, f64::MIN_POSITIVE);
+  for value in [1, 2, 3] {
+    heap.push(value);
+    assert_eq!(Some(value), heap.get_median());
+  }
+  assert!(!heap.is_empty());
+}
+
+#[test]
+fn test_median_heap_summary() {
+  let heap: MedianHeap<i32, IntegerMidpointMedian> = MedianHeap::empty();
+  assert_eq!(MedianSummary { count: 0, median: None, min: None, max: None, mean: None, p90: None, p99: None }, heap.summary());
+
+  let values: Vec<i32> = (0..1000).map(|x| (x * 37) % 1000).collect();
+  let heap: MedianHeap<i32, IntegerMidpointMedian> = values.into_iter().collect();
+  let summary = heap.summary();
+  assert_eq!(1000, summary.count);
+  assert_eq!(heap.get_median(), summary.median);
+  assert_eq!(heap.min().copied(), summary.min);
+  assert_eq!(heap.max().copied(), summary.max);
+  assert_eq!(heap.checked_mean(), summary.mean);
+  assert_eq!(heap.get_sorted(899).copied(), summary.p90);
+  assert_eq!(heap.get_sorted(989).copied(), summary.p99);
+}
+
+#[test]
+fn test_median_heap_append_iter_matches_extend() {
+  let values = [9, 2, 7, 2, 5, 11, 0];
+  for initial in [vec![], vec![4], vec![3, 8, 1]] {
+    let mut appended: MedianHeap<i32, IntegerMidpointMedian> = initial.clone().into_iter().collect();
+    let mut extended = appended.clone();
+    appended.append_iter(values);
+    extended.extend(values);
+    assert_eq!(extended.halves(), appended.halves());
+    assert_eq!(extended.get_median(), appended.get_median());
+    assert_eq!(extended.min(), appended.min());
+    assert_eq!(extended.max(), appended.max());
+  }
+}
+
+#[test]
+#[cfg(feature = "testing")]
+fn test_testing_helpers() {
+  use crate::testing::{assert_median_eq, heap_from};
+
+  let mut heap = heap_from(&[9, 1, 5, 3]);
+  assert_eq!(vec![1, 3, 5, 9], heap.to_sorted_vec());
+  assert_median_eq(&heap, 4);
+
+  heap.push(4);
+  assert_median_eq(&heap, 4);
+  assert!(heap_from::<i32>(&[]).is_empty());
+}
+
+#[test]
+#[cfg(feature = "testing")]
+#[should_panic(expected = "unexpected median of the values [1, 2, 3]")]
+fn test_testing_assert_median_eq_fails() {
+  crate::testing::assert_median_eq(&crate::testing::heap_from(&[3, 1, 2]), 3);
+}
+
+// An operation of the randomized model test, applied to a MedianHeap and to a Vec holding the same values.
+#[derive(Clone, Debug)]
+enum ModelOp {
+  Push(i32),
+  Pop,
+  Delete(i32),
+  TryDelete(i32),
+  GetMedian,
+}
+
+fn model_ops(range: i32, len: usize) -> impl proptest::strategy::Strategy<Value = Vec<ModelOp>> {
+  use proptest::strategy::{Just, Strategy};
+
+  let op = proptest::prop_oneof![
+    4 => (-range..range).prop_map(ModelOp::Push),
+    1 => Just(ModelOp::Pop),
+    1 => (-range..range).prop_map(ModelOp::Delete),
+    1 => (-range..range).prop_map(ModelOp::TryDelete),
+    1 => Just(ModelOp::GetMedian),
+  ];
+  proptest::collection::vec(op, 0..len)
+}
+
+// Applies the operations to the heap and to a Vec-based reference, panicking as soon as they disagree.
+fn check_against_model<K: MergeMedian<i32> + Clone>(mut heap: MedianHeap<i32, K>, ops: &[ModelOp]) {
+  let kind = heap.median_kind().clone();
+  let mut reference: Vec<i32> = Vec::new();
+  for (step, op) in ops.iter().enumerate() {
+    match *op {
+      ModelOp::Push(value) => {
+        heap.push(value);
+        reference.push(value);
+      }
+      ModelOp::Pop => {
+        let expected = sorted_median(&kind, &reference);
+        reference.sort();
+        let n = reference.len();
+        if n > 0 {
+          // The middle value is removed, or both middle values if the number of values is even.
+          reference.drain((n - 1) / 2..=n / 2);
+        }
+        assert_eq!(expected, heap.pop(), "pop at step {}", step);
+      }
+      ModelOp::Delete(value) => {
+        heap.delete(&value);
+        reference.retain(|x| *x != value);
+      }
+      ModelOp::TryDelete(value) => {
+        let expected = match reference.iter().position(|x| *x == value) {
+          Some(index) => Ok(reference.swap_remove(index)),
+          None if reference.is_empty() => Err(MedianHeapError::Empty),
+          None => Err(MedianHeapError::NotFound),
+        };
+        assert_eq!(expected, heap.try_delete(&value), "try_delete at step {}", step);
+      }
+      ModelOp::GetMedian => {}
+    }
+
+    assert_eq!(reference.len(), heap.len(), "length after step {} ({:?})", step, op);
+    assert_eq!(sorted_median(&kind, &reference), heap.get_median(), "median after step {} ({:?})", step, op);
+    assert_eq!(reference.iter().min(), heap.min(), "minimum after step {} ({:?})", step, op);
+    assert_eq!(reference.iter().max(), heap.max(), "maximum after step {} ({:?})", step, op);
+    assert_eq!(Ok(()), heap.validate(), "invariants after step {} ({:?})", step, op);
+  }
+}
+
+proptest::proptest! {
+  #[test]
+  fn prop_model_with_duplicates(ops in model_ops(3, 300)) {
+    check_against_model(MedianHeap::new(IntegerMidpointMedian), &ops);
+    check_against_model(MedianHeap::new(LeftHandedMedian), &ops);
+    check_against_model(MedianHeap::new(RightHandedMedian), &ops);
+  }
+
+  #[test]
+  fn prop_model_wide_range(ops in model_ops(1000, 300)) {
+    check_against_model(MedianHeap::new(IntegerMidpointMedian), &ops);
+  }
+
+  #[test]
+  fn prop_model_with_slack_and_auto_capacity(slack in 1..5usize, ops in model_ops(20, 300)) {
+    check_against_model(MedianHeap::with_slack(IntegerMidpointMedian, slack), &ops);
+    let config = AutoCapacity { shrink_below: 0.5, shrink_after: 3, min_capacity: 0 };
+    check_against_model(MedianHeap::with_auto_capacity(LeftHandedMedian, config), &ops);
+  }
+}
+
+#[test]
+fn test_model_regressions() {
+  use ModelOp::*;
+
+  // Deleting every copy of a value that sits on both sides of the median has to rebalance the heap.
+  check_against_model(MedianHeap::new(IntegerMidpointMedian), &[Push(2), Push(5), Push(0), Push(0), Push(2), Push(2), Push(0), Delete(2)]);
+  // A merged median isn't stored, so deleting it removes nothing.
+  check_against_model(MedianHeap::new(IntegerMidpointMedian), &[Push(1), Push(3), Delete(2), TryDelete(2), Push(3), Push(3), Delete(3)]);
+  // Popping two equal middle values of an even number of duplicates.
+  check_against_model(MedianHeap::new(LeftHandedMedian), &[Push(1), Push(1), Push(1), Push(1), Pop, Pop, Pop]);
+  // Removing a single copy of an extreme forgets the cached extreme only once the last copy is gone.
+  check_against_model(MedianHeap::new(RightHandedMedian), &[Push(9), Push(9), Push(0), TryDelete(9), GetMedian, TryDelete(9), Pop]);
+}
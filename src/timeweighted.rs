@@ -0,0 +1,121 @@
+use std::collections::BTreeMap;
+
+use crate::MergeMedian;
+
+/// TimeWeightedMedianHeap is a struct that keeps track of the time-weighted median of an irregularly sampled signal.
+/// Every value weighs proportionally to the duration it was the current reading, which is the time between its timestamp and the timestamp of the next value.
+/// The time-weighted median is the value that splits the total elapsed time in half.
+///
+/// The most recent value has no weight until the next value is pushed, since its duration isn't known yet.
+/// If two values share a timestamp, the first one was the current reading for no time at all and gets no weight.
+/// Timestamps are expected to be non-decreasing, a timestamp older than the previous one is treated as if no time elapsed.
+///
+/// Like MedianHeap, the struct takes a type K implementing the MergeMedian trait.
+/// It's used when the total elapsed time is split exactly between two values.
+///
+/// Example:
+/// ```
+/// use median_heap::{LeftHandedMedian, TimeWeightedMedianHeap};
+///
+/// let mut heap = TimeWeightedMedianHeap::new(LeftHandedMedian);
+/// heap.push_at(10, 0);
+/// heap.push_at(20, 8);
+/// heap.push_at(30, 9);
+/// heap.push_at(40, 10);
+///
+/// // 10 was the current reading for 8 of the 10 elapsed time units.
+/// assert_eq!(10, heap.get_median().unwrap());
+/// ```
+#[derive(Clone, Debug)]
+pub struct TimeWeightedMedianHeap<T, K> {
+  median_kind: K,
+  durations: BTreeMap<T, u64>,
+  total_duration: u64,
+  current: Option<(T, u64)>,
+  count: usize,
+}
+
+impl<T: Ord + Copy, K: MergeMedian<T>> TimeWeightedMedianHeap<T, K> {
+  /// Creates a new TimeWeightedMedianHeap instance with the specified median kind.
+  pub fn new(median_kind: K) -> Self {
+    TimeWeightedMedianHeap {
+      median_kind,
+      durations: BTreeMap::new(),
+      total_duration: 0,
+      current: None,
+      count: 0,
+    }
+  }
+
+  /// Adds a value that became the current reading at the specified timestamp.
+  /// The previous reading is weighted by the time elapsed since its own timestamp.
+  ///
+  /// # Complexity
+  /// O(logn)
+  pub fn push_at(&mut self, value: T, timestamp: u64) {
+    if let Some((previous, previous_timestamp)) = self.current {
+      let elapsed = timestamp.saturating_sub(previous_timestamp);
+      if elapsed > 0 {
+        *self.durations.entry(previous).or_insert(0) += elapsed;
+        self.total_duration += elapsed;
+      }
+    }
+
+    self.current = Some((value, timestamp.max(self.current.map_or(0, |(_, t)| t))));
+    self.count += 1;
+  }
+
+  /// Returns the time-weighted median of the values in the heap.
+  /// If no time has elapsed yet, the most recent value is returned.
+  /// If the heap is empty, the method returns None.
+  ///
+  /// # Complexity
+  /// O(n) in the number of distinct values
+  pub fn get_median(&self) -> Option<T> {
+    let (current, _) = self.current?;
+    if self.total_duration == 0 {
+      return Some(current)
+    }
+
+    let mut elapsed = 0;
+    let mut values = self.durations.iter();
+    while let Some((value, duration)) = values.next() {
+      elapsed += duration;
+      if elapsed * 2 == self.total_duration {
+        // The elapsed time is split exactly between this value and the next one.
+        let next = values.next().map_or(value, |(next, _)| next);
+        return Some(self.median_kind.merge(value, next))
+      }
+      if elapsed * 2 > self.total_duration {
+        return Some(*value)
+      }
+    }
+
+    None
+  }
+}
+
+impl<T, K> TimeWeightedMedianHeap<T, K> {
+  /// Returns the number of values pushed to the heap.
+  pub fn len(&self) -> usize {
+    self.count
+  }
+
+  /// Returns true if no values have been pushed to the heap, false otherwise.
+  pub fn is_empty(&self) -> bool {
+    self.count == 0
+  }
+
+  /// Returns the total time covered by the weighted values, from the first timestamp to the most recent one.
+  pub fn total_duration(&self) -> u64 {
+    self.total_duration
+  }
+
+  /// Removes all values from the heap.
+  pub fn clear(&mut self) {
+    self.durations.clear();
+    self.total_duration = 0;
+    self.current = None;
+    self.count = 0;
+  }
+}
@@ -0,0 +1,128 @@
+use std::collections::VecDeque;
+
+use crate::{MedianHeap, MergeMedian};
+
+/// TimeWindowMedianHeap is a struct that keeps track of the median of the values pushed within a sliding time window.
+/// Every value carries a timestamp, and values whose timestamp is more than the window duration older than the newest timestamp are evicted.
+/// This is the time-based counterpart of a MedianHeap created with EvictionPolicy::EvictOldest, which keeps a fixed number of values.
+///
+/// Timestamps are expected to be mostly non-decreasing, but clock regressions are handled gracefully.
+/// A value with a timestamp older than the newest one is inserted in timestamp order without evicting anything, as long as it still falls inside the window.
+/// A value that already falls outside the window is rejected.
+///
+/// Example:
+/// ```
+/// use median_heap::{IntegerMidpointMedian, TimeWindowMedianHeap};
+///
+/// let mut heap = TimeWindowMedianHeap::new(IntegerMidpointMedian, 10);
+/// heap.push_at(1, 0);
+/// heap.push_at(2, 5);
+/// heap.push_at(9, 10);
+/// assert_eq!(2, heap.get_median().unwrap());
+///
+/// // The value pushed at 0 is now more than 10 time units old.
+/// heap.push_at(10, 11);
+/// assert_eq!(3, heap.len());
+/// assert_eq!(9, heap.get_median().unwrap());
+/// ```
+pub struct TimeWindowMedianHeap<T, K> {
+  heap: MedianHeap<T, K>,
+  duration: u64,
+  // The values in the window together with their timestamps, in timestamp order.
+  entries: VecDeque<(T, u64)>,
+}
+
+impl<T: Ord + Copy, K: MergeMedian<T>> TimeWindowMedianHeap<T, K> {
+  /// Creates a new TimeWindowMedianHeap instance with the specified median kind that keeps values for the specified duration.
+  pub fn new(median_kind: K, duration: u64) -> Self {
+    TimeWindowMedianHeap {
+      heap: MedianHeap::new(median_kind),
+      duration,
+      entries: VecDeque::new(),
+    }
+  }
+
+  /// Adds a value pushed at the specified timestamp and evicts every value that fell out of the window.
+  /// Returns false if the value was rejected because its timestamp already lies outside the window, true otherwise.
+  ///
+  /// # Complexity
+  /// O(n) per evicted value, O(logn) if nothing is evicted and the timestamp isn't older than the newest one
+  pub fn push_at(&mut self, value: T, timestamp: u64) -> bool {
+    let newest = self.newest_timestamp().unwrap_or(timestamp);
+    if timestamp < newest {
+      if newest - timestamp > self.duration {
+        return false
+      }
+
+      // Clock regression, insert the value in timestamp order without evicting anything.
+      let index = self.entries.partition_point(|(_, t)| *t <= timestamp);
+      self.entries.insert(index, (value, timestamp));
+      self.heap.push(value);
+      return true
+    }
+
+    self.entries.push_back((value, timestamp));
+    self.heap.push(value);
+    while let Some(&(oldest, oldest_timestamp)) = self.entries.front() {
+      if timestamp - oldest_timestamp <= self.duration {
+        break;
+      }
+      self.entries.pop_front();
+      let _ = self.heap.try_delete(&oldest);
+    }
+    true
+  }
+
+  /// Returns the median of the values within the window.
+  /// If the window is empty, the method returns None.
+  ///
+  /// # Complexity
+  /// O(1)
+  pub fn get_median(&self) -> Option<T> {
+    self.heap.get_median()
+  }
+}
+
+impl<T, K> TimeWindowMedianHeap<T, K> {
+  /// Returns the number of values within the window.
+  pub fn len(&self) -> usize {
+    self.entries.len()
+  }
+
+  /// Returns true if the window doesn't contain any values, false otherwise.
+  pub fn is_empty(&self) -> bool {
+    self.entries.is_empty()
+  }
+
+  /// Returns the duration of the window.
+  pub fn duration(&self) -> u64 {
+    self.duration
+  }
+
+  /// Returns the newest timestamp within the window.
+  /// If the window is empty, the method returns None.
+  pub fn newest_timestamp(&self) -> Option<u64> {
+    self.entries.back().map(|(_, timestamp)| *timestamp)
+  }
+
+  /// Returns a reference to the MedianHeap holding the values within the window, to run any of its queries.
+  pub fn heap(&self) -> &MedianHeap<T, K> {
+    &self.heap
+  }
+
+  /// Removes all values from the window.
+  pub fn clear(&mut self) {
+    self.heap.clear();
+    self.entries.clear();
+  }
+}
+
+impl<T: Ord + Clone, K: Clone> Clone for TimeWindowMedianHeap<T, K> {
+  fn clone(&self) -> Self {
+    TimeWindowMedianHeap {
+      heap: self.heap.clone(),
+      duration: self.duration,
+      entries: self.entries.clone(),
+    }
+  }
+}